@@ -26,7 +26,7 @@ extern "C" {
 #endif
 
 // String structure for cross-FFI string passing
-typedef struct {
+typedef struct MTString {
     uint8_t* ptr;
     uintptr_t len;
 } MTString;
@@ -57,6 +57,19 @@ typedef struct {
     bool valid;
 } MTBandRange;
 
+// Text edit: UTF-16 range of the original text and its replacement
+typedef struct MTTextEdit {
+    uint32_t start;
+    uint32_t end;
+    MTString replacement;
+} MTTextEdit;
+
+// Edit list owned by the core; release with mind_type_free_edits
+typedef struct MTTextEditList {
+    MTTextEdit* ptr;
+    uintptr_t len;
+} MTTextEditList;
+
 // Core version and memory management
 MTString mind_type_core_version(void);
 void mind_type_core_free_string(MTString s);
@@ -75,9 +88,18 @@ uint32_t mind_type_caret_monitor_get_snapshots(
 // Text processing functions
 MTString mind_type_extract_fragment(const uint8_t* text_ptr, uintptr_t text_len);
 MTBandRange mind_type_compute_band(const uint8_t* text_ptr, uintptr_t text_len, uint32_t caret);
+MTTextEditList mind_type_caret_safe_diff(
+    const uint8_t* original_ptr,
+    uintptr_t original_len,
+    const uint8_t* modified_ptr,
+    uintptr_t modified_len,
+    uint32_t caret
+);
+void mind_type_free_edits(MTTextEditList list);
 
 // Configuration functions
 bool mind_type_set_tone(bool enabled, const uint8_t* target_ptr, uintptr_t target_len);
+bool mind_type_set_keyboard_layout(const uint8_t* name_ptr, uintptr_t name_len);
 
 #ifdef __cplusplus
 }
@@ -40,6 +40,17 @@ public struct MTBandRange {
     var valid: Bool
 }
 
+public struct MTTextEdit {
+    var start: UInt32
+    var end: UInt32
+    var replacement: MTString
+}
+
+public struct MTTextEditList {
+    var ptr: UnsafeMutablePointer<MTTextEdit>?
+    var len: UInt
+}
+
 // C function declarations
 @_silgen_name("mind_type_core_version")
 func mind_type_core_version() -> MTString
@@ -72,9 +83,24 @@ func mind_type_extract_fragment(_ text_ptr: UnsafePointer<UInt8>?, _ text_len: U
 @_silgen_name("mind_type_compute_band")
 func mind_type_compute_band(_ text_ptr: UnsafePointer<UInt8>?, _ text_len: UInt, _ caret: UInt32) -> MTBandRange
 
+@_silgen_name("mind_type_caret_safe_diff")
+func mind_type_caret_safe_diff(
+    _ original_ptr: UnsafePointer<UInt8>?,
+    _ original_len: UInt,
+    _ modified_ptr: UnsafePointer<UInt8>?,
+    _ modified_len: UInt,
+    _ caret: UInt32
+) -> MTTextEditList
+
+@_silgen_name("mind_type_free_edits")
+func mind_type_free_edits(_ list: MTTextEditList)
+
 @_silgen_name("mind_type_set_tone")
 func mind_type_set_tone(_ enabled: Bool, _ target_ptr: UnsafePointer<UInt8>?, _ target_len: UInt) -> Bool
 
+@_silgen_name("mind_type_set_keyboard_layout")
+func mind_type_set_keyboard_layout(_ name_ptr: UnsafePointer<UInt8>?, _ name_len: UInt) -> Bool
+
 // Swift enums for type safety
 public enum CaretEventKind: UInt32 {
     case typing = 0
@@ -121,6 +147,13 @@ public struct BandRange {
     }
 }
 
+public struct TextEdit {
+    /// UTF-16 range of the original text
+    public let start: UInt32
+    public let end: UInt32
+    public let replacement: String
+}
+
 public class FFIBridge {
     private var caretMonitor: UnsafeMutableRawPointer?
     
@@ -217,6 +250,32 @@ public class FFIBridge {
         }
     }
     
+    // Caret-safe edits turning `original` into `modified` (caret and ranges in UTF-16 units)
+    public func caretSafeDiff(original: String, modified: String, caret: Int) -> [TextEdit] {
+        let list = original.withCString { originalPtr in
+            modified.withCString { modifiedPtr in
+                mind_type_caret_safe_diff(
+                    UnsafePointer(originalPtr),
+                    UInt(original.utf8.count),
+                    UnsafePointer(modifiedPtr),
+                    UInt(modified.utf8.count),
+                    UInt32(caret)
+                )
+            }
+        }
+        defer { mind_type_free_edits(list) }
+        guard let ptr = list.ptr else { return [] }
+
+        return (0..<Int(list.len)).map { i in
+            let edit = ptr[i]
+            var replacement = ""
+            if let bytes = edit.replacement.ptr, edit.replacement.len > 0 {
+                replacement = String(data: Data(bytes: bytes, count: Int(edit.replacement.len)), encoding: .utf8) ?? ""
+            }
+            return TextEdit(start: edit.start, end: edit.end, replacement: replacement)
+        }
+    }
+    
     // Set tone configuration
     public func setTone(enabled: Bool, target: String) -> Bool {
         return target.withCString { targetPtr in
@@ -224,6 +283,13 @@ public class FFIBridge {
         }
     }
     
+    // Set keyboard layout ("QWERTY", "QWERTZ", "AZERTY" or "Dvorak")
+    public func setKeyboardLayout(_ name: String) -> Bool {
+        return name.withCString { namePtr in
+            return mind_type_set_keyboard_layout(UnsafePointer(namePtr), UInt(name.utf8.count))
+        }
+    }
+    
     // Convenience method for processing text changes
     public func processTextChange(
         text: String,
@@ -12,27 +12,40 @@
   ╚══════════════════════════════════════════════════════════╝
   • WHAT ▸ No edits at or after the caret; Active region policy (render vs context ranges)
   • WHY  ▸ REQ-IME-CARETSAFE, CONTRACT-ACTIVE-REGION
-  • HOW  ▸ See linked contracts and guides in docs
+  • HOW  ▸ Walk back N Unicode words from the caret; snap to sentence starts
 */
 
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::fragment::FragmentExtractor;
+use crate::offsets::floor_char_boundary;
+
 /// Represents an active region in text
+///
+/// `start..end` is the render range: the only span corrections may touch.
+/// `context` covers it plus preceding text the model may read.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ActiveRegion {
     /// Start position in text
     pub start: usize,
-    /// End position in text
+    /// End position in text (never past the caret)
     pub end: usize,
     /// Number of words in region
     pub word_count: usize,
+    /// Read-only context range; always contains the render range
+    pub context: Range<usize>,
 }
 
 impl ActiveRegion {
-    /// Create a new active region
+    /// Create a new active region whose context equals its render range
     pub fn new(start: usize, end: usize, word_count: usize) -> Self {
         Self {
             start,
             end,
             word_count,
+            context: start..end,
         }
     }
 
@@ -45,28 +58,91 @@ impl ActiveRegion {
     pub fn length(&self) -> usize {
         self.end.saturating_sub(self.start)
     }
+
+    pub fn render_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
 }
 
 /// Active region policy for calculating boundaries
 pub struct ActiveRegionPolicy {
     /// Target number of words in region
     pub target_words: usize,
+    /// Move the start to a sentence start this many words away or fewer
+    pub snap_words: usize,
+    /// Bytes of context read before the render range (same default as the TS LM policy)
+    pub context_left_bytes: usize,
 }
 
 impl ActiveRegionPolicy {
     /// Create a new policy with target word count
     pub fn new(target_words: usize) -> Self {
-        Self { target_words }
+        Self {
+            target_words,
+            snap_words: 3,
+            context_left_bytes: 60,
+        }
     }
 
-    /// Calculate active region around caret position
+    /// Calculate the active region behind the caret (byte offsets)
     pub fn calculate_region(&self, text: &str, caret: usize) -> ActiveRegion {
-        // Stub implementation - returns simple region around caret
-        let start = caret.saturating_sub(50).min(text.len());
-        let end = (caret + 50).min(text.len());
-        let word_count = text[start..end].split_whitespace().count();
-        
-        ActiveRegion::new(start, end, word_count)
+        let caret = floor_char_boundary(text, caret);
+        let prefix = &text[..caret];
+        // The render range never crosses a line break
+        let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+        let line = &prefix[line_start..];
+
+        // Word starts walking back from the caret, nearest first
+        let limit = self.target_words.max(1) + self.snap_words;
+        let word_starts: Vec<usize> = line
+            .split_word_bound_indices()
+            .rev()
+            .filter(|(_, w)| w.chars().any(char::is_alphanumeric))
+            .map(|(i, _)| line_start + i)
+            .take(limit)
+            .collect();
+        if word_starts.is_empty() {
+            return ActiveRegion::new(caret, caret, 0);
+        }
+        let words = word_starts.len().min(self.target_words.max(1));
+        let mut start = word_starts[words - 1];
+
+        // Only sentence starts inside the window count; the window's own
+        // first segment is a real start only at a line/document boundary
+        let window_start = *word_starts.last().unwrap_or(&start);
+        let sentence_starts = FragmentExtractor::new()
+            .segment(&text[window_start..caret])
+            .into_iter()
+            .map(|s| window_start + s.range.start)
+            .filter(|&s| s > window_start || word_starts.len() < limit);
+        let words_after = |pos: usize| word_starts.iter().filter(|&&w| w >= pos).count();
+        let snap = sentence_starts
+            .map(|s| (s, words_after(s)))
+            .filter(|&(_, n)| n > 0 && n.abs_diff(words) <= self.snap_words)
+            .min_by_key(|&(s, n)| (n.abs_diff(words), usize::MAX - s));
+        if let Some((s, _)) = snap {
+            start = s;
+        }
+
+        let mut region = ActiveRegion::new(start, caret, words_after(start));
+        region.context = self.context_start(text, start)..caret;
+        region
+    }
+
+    /// Up to `context_left_bytes` before `start`, not beginning mid-word
+    fn context_start(&self, text: &str, start: usize) -> usize {
+        let from = floor_char_boundary(text, start.saturating_sub(self.context_left_bytes));
+        let mid_word = text[..from].chars().next_back().is_some_and(char::is_alphanumeric)
+            && text[from..].chars().next().is_some_and(char::is_alphanumeric);
+        if !mid_word {
+            return from;
+        }
+        text[from..start]
+            .split_word_bound_indices()
+            .skip(1)
+            .find(|(_, w)| !w.trim().is_empty())
+            .map(|(i, _)| from + i)
+            .unwrap_or(start)
     }
 }
 
@@ -74,4 +150,4 @@ impl Default for ActiveRegionPolicy {
     fn default() -> Self {
         Self::new(20) // Default to 20 words as specified in PRD
     }
-}
\ No newline at end of file
+}
@@ -44,7 +44,7 @@ pub enum CaretPrimaryState {
     Autocorrect,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum InputModality {
     Keyboard,
@@ -53,27 +53,21 @@ pub enum InputModality {
     Paste,
     Drop,
     Programmatic,
+    #[default]
     Unknown,
 }
 
-impl Default for InputModality {
-    fn default() -> Self { InputModality::Unknown }
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum FieldKind {
     InputText,
     TextArea,
     ContentEditable,
     Password,
+    #[default]
     Other,
 }
 
-impl Default for FieldKind {
-    fn default() -> Self { FieldKind::Other }
-}
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DeviceTier {
@@ -83,14 +77,14 @@ pub enum DeviceTier {
     Native,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
 pub struct SelectionFacet {
     pub collapsed: bool,
     pub start: u32,
     pub end: u32,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EventKind {
     FocusIn,
@@ -114,10 +108,11 @@ pub enum EventKind {
     Autocorrect,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CaretEvent {
     pub kind: EventKind,
     pub timestamp_ms: u64,
+    // Caret and text length are UTF-16 code units, as reported by DOM/NSString hosts
     pub caret: u32,
     pub text_len: u32,
     #[serde(default)]
@@ -135,7 +130,19 @@ pub struct CaretEvent {
     pub input_type: Option<String>,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+impl CaretEvent {
+    /// Caret as a UTF-8 byte offset into `text` (clamped to a char boundary)
+    pub fn caret_byte_offset(&self, text: &str) -> usize {
+        crate::offsets::utf16_to_byte_clamped(text, self.caret as usize)
+    }
+
+    /// Undo (not redo), from the event kind or the DOM "historyUndo" hint
+    pub fn is_undo(&self) -> bool {
+        self.kind == EventKind::Undo || self.input_type.as_deref() == Some("historyUndo")
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CaretSnapshot {
     pub primary: CaretPrimaryState,
     pub input_modality: InputModality,
@@ -361,8 +368,7 @@ impl CaretMonitor {
     }
 
     fn caret_jump(prev: u32, next: u32, threshold: u32) -> bool {
-        let d = if next >= prev { next - prev } else { prev - next };
-        d >= threshold
+        prev.abs_diff(next) >= threshold
     }
 
     fn equal_snapshots(a: &CaretSnapshot, b: &CaretSnapshot) -> bool {
@@ -456,11 +462,11 @@ impl CaretMonitor {
 
         // Caret jump detection (collapsed selection moves beyond threshold)
         let mut caret_jump = false;
-        if ev.selection.collapsed {
-            if Self::caret_jump(self.last_caret, ev.caret, self.thresholds.jump_threshold_chars) {
-                caret_jump = true;
-                self.stats.caret_jumps += 1;
-            }
+        if ev.selection.collapsed
+            && Self::caret_jump(self.last_caret, ev.caret, self.thresholds.jump_threshold_chars)
+        {
+            caret_jump = true;
+            self.stats.caret_jumps += 1;
         }
         self.last_caret = ev.caret;
 
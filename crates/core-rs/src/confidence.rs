@@ -1,21 +1,26 @@
-/*
-╔══════════════════════════════════════════════════════════════╗
-║  ░  CONFIDENCE  T H R E S H O L D S  &  A D A P T I O N   ░░  ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-║           ╌╌  P L A C E H O L D E R  ╌╌                      ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-╚══════════════════════════════════════════════════════════════╝
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  C O N F I D E N C E   G A T E  ░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Multi-signal scores and hold / commit / discard gating.    ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
   • WHAT ▸ Compute dynamic thresholds vs caret distance and edit class
   • WHY  ▸ Gate edits to preserve trust; adapt using undo signals
+           (REQ-CONFIDENCE-GATE, port of core/confidenceGate.ts)
   • HOW  ▸ Stateless calculators + small state for adaptation
 */
 
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::caret_monitor::CaretEvent;
+use crate::default_thresholds::MAX_SWEEP_WINDOW;
+use crate::offsets::floor_char_boundary;
+
 pub struct ConfidenceConfig {
     pub base_threshold: f32,
 }
@@ -26,5 +31,380 @@ pub fn threshold_for_distance(cfg: &ConfidenceConfig, distance_chars: usize) ->
     t
 }
 
+/// Signals behind a confidence score; each is 0..1
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConfidenceInputs {
+    /// Share of letters/digits in the sample (garbled input scores low)
+    pub input_fidelity: f32,
+    /// How sure the stage is about its rewrite
+    pub transformation_quality: f32,
+    /// Fit with the surrounding sentences
+    pub context_coherence: f32,
+    /// 1 for fresh proposals, falling as they age (see `temporal_decay`)
+    pub temporal_decay: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConfidenceScore {
+    /// Clamped copy of the inputs
+    pub inputs: ConfidenceInputs,
+    /// Weighted sum of the inputs
+    pub combined: f32,
+}
+
+const WEIGHT_INPUT_FIDELITY: f32 = 0.3;
+const WEIGHT_TRANSFORMATION_QUALITY: f32 = 0.4;
+const WEIGHT_CONTEXT_COHERENCE: f32 = 0.2;
+const WEIGHT_TEMPORAL_DECAY: f32 = 0.1;
+
+/// Clamp to 0..1; NaN and infinities become 0
+pub fn clamp01(x: f32) -> f32 {
+    if x.is_finite() { x.clamp(0.0, 1.0) } else { 0.0 }
+}
+
+/// Share of letters and digits among the non-whitespace characters of `sample`
+pub fn compute_input_fidelity(sample: &str) -> f32 {
+    let (good, total) = sample
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .fold((0usize, 0usize), |(good, total), c| (good + c.is_alphanumeric() as usize, total + 1));
+    if total == 0 { 0.0 } else { clamp01(good as f32 / total as f32) }
+}
+
+/// Exponential decay: 1 when fresh, 0.5 after `half_life_ms`
+pub fn temporal_decay(age_ms: u64, half_life_ms: u64) -> f32 {
+    if half_life_ms == 0 {
+        return 0.0;
+    }
+    clamp01(0.5f32.powf(age_ms as f32 / half_life_ms as f32))
+}
+
+pub fn compute_confidence(inputs: ConfidenceInputs) -> ConfidenceScore {
+    let inputs = ConfidenceInputs {
+        input_fidelity: clamp01(inputs.input_fidelity),
+        transformation_quality: clamp01(inputs.transformation_quality),
+        context_coherence: clamp01(inputs.context_coherence),
+        temporal_decay: clamp01(inputs.temporal_decay),
+    };
+    let combined = inputs.input_fidelity * WEIGHT_INPUT_FIDELITY
+        + inputs.transformation_quality * WEIGHT_TRANSFORMATION_QUALITY
+        + inputs.context_coherence * WEIGHT_CONTEXT_COHERENCE
+        + inputs.temporal_decay * WEIGHT_TEMPORAL_DECAY;
+    ConfidenceScore { inputs, combined }
+}
+
+/// Score a stage proposal over `range` of `text`. Stages report one confidence,
+/// which stands for both their rewrite and its fit; input fidelity comes from the
+/// text the stage looked at, its sweep window up to the caret.
+pub fn score_proposal(text: &str, caret: usize, range: &Range<usize>, confidence: f32, decay: f32) -> ConfidenceScore {
+    let from = floor_char_boundary(text, range.start.saturating_sub(MAX_SWEEP_WINDOW));
+    let sample = text.get(from..floor_char_boundary(text, caret.max(range.end))).unwrap_or_default();
+    compute_confidence(ConfidenceInputs {
+        input_fidelity: compute_input_fidelity(sample),
+        transformation_quality: confidence,
+        context_coherence: confidence,
+        temporal_decay: decay,
+    })
+}
+
+/// τ thresholds of the gate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GateThresholds {
+    /// τ_input: minimum input fidelity before anything commits
+    pub input: f32,
+    /// τ_commit: minimum combined score to apply a proposal
+    pub commit: f32,
+    /// τ_tone: tone proposals also need this transformation quality
+    pub tone: f32,
+    /// τ_discard: below this combined score proposals are dropped
+    pub discard: f32,
+}
+
+impl Default for GateThresholds {
+    fn default() -> Self {
+        Self { input: 0.55, commit: 0.8, tone: 0.75, discard: 0.3 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateDecision {
+    Hold,
+    Commit,
+    Discard,
+}
+
+/// Decide a proposal; `require_tone` adds the τ_tone check on transformation quality
+pub fn apply_thresholds(score: &ConfidenceScore, thresholds: &GateThresholds, require_tone: bool) -> GateDecision {
+    if score.combined < thresholds.discard {
+        return GateDecision::Discard;
+    }
+    if score.inputs.input_fidelity < thresholds.input {
+        return GateDecision::Hold;
+    }
+    let tone_ok = !require_tone || score.inputs.transformation_quality >= thresholds.tone;
+    if score.combined >= thresholds.commit && tone_ok {
+        GateDecision::Commit
+    } else {
+        GateDecision::Hold
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditType {
+    Noise,
+    Lm,
+    Context,
+    Tone,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicThresholdsInput {
+    /// Byte caret
+    pub caret: usize,
+    /// Byte range of the proposal
+    pub range: Range<usize>,
+    pub edit_type: EditType,
+    /// Multiplier for the near-caret and undo boosts (default 1.0; 0.1..=5)
+    pub sensitivity: f32,
+    /// Rollbacks/undos seen recently
+    pub recent_rollbacks: u32,
+    /// Time since the last rollback; `None` when there was none
+    pub last_rollback_ms_ago: Option<u64>,
+    pub base: GateThresholds,
+}
+
+impl DynamicThresholdsInput {
+    pub fn new(caret: usize, range: Range<usize>, edit_type: EditType) -> Self {
+        Self {
+            caret,
+            range,
+            edit_type,
+            sensitivity: 1.0,
+            recent_rollbacks: 0,
+            last_rollback_ms_ago: None,
+            base: GateThresholds::default(),
+        }
+    }
+}
+
+/// Bytes from the end of `range` back to the caret; 0 when the caret is inside or before it
+pub fn distance_from_caret(caret: usize, range: &Range<usize>) -> usize {
+    caret.saturating_sub(range.start.max(range.end))
+}
+
+/// Rollbacks in the last 3 s make commits stricter, fading linearly
+fn undo_boost(count: u32, ms_ago: Option<u64>, sensitivity: f32) -> f32 {
+    const WINDOW_MS: f32 = 3_000.0;
+    match ms_ago {
+        Some(ms) if count > 0 && (ms as f32) <= WINDOW_MS => {
+            let boost = 0.03 + count.saturating_sub(1) as f32 * 0.02;
+            boost * (1.0 - ms as f32 / WINDOW_MS) * sensitivity
+        }
+        _ => 0.0,
+    }
+}
+
+/// Thresholds for one proposal: stricter near the caret and after undos,
+/// looser for noise, stricter for tone (`computeDynamicThresholds`)
+pub fn compute_dynamic_thresholds(input: &DynamicThresholdsInput) -> GateThresholds {
+    let base = input.base;
+    let sensitivity = if input.sensitivity.is_finite() && input.sensitivity > 0.0 { input.sensitivity.clamp(0.1, 5.0) } else { 1.0 };
+    let distance = distance_from_caret(input.caret, &input.range);
+    let near = 1.0 - (distance as f32 / MAX_SWEEP_WINDOW as f32).min(1.0);
+
+    let (offset_commit, offset_input) = match input.edit_type {
+        EditType::Noise => (-0.05, -0.02),
+        EditType::Tone => (0.03, 0.0),
+        EditType::Lm | EditType::Context => (0.0, 0.0),
+    };
+    let undo = undo_boost(input.recent_rollbacks, input.last_rollback_ms_ago, sensitivity);
+
+    let mut threshold_input = clamp01(base.input + 0.02 * near * sensitivity + offset_input);
+    let mut commit = (base.commit + 0.05 * near * sensitivity + offset_commit + undo).clamp(0.0, 0.98);
+    if threshold_input > commit {
+        // Nudge commit up; input gives way only at the 0.98 ceiling
+        commit = threshold_input.min(0.98);
+        threshold_input = commit;
+    }
+    let mut discard = clamp01(base.discard);
+    if discard >= threshold_input {
+        discard = (threshold_input - 0.05).max(0.0);
+    }
+    GateThresholds { input: threshold_input, commit, tone: clamp01(base.tone), discard }
+}
+
+// ────────────────────────────────────────────────────────────────
+// Undo-driven adaptation
+// ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveConfig {
+    /// An undo this soon after a system edit counts against it
+    pub undo_window_ms: u64,
+    /// Commit-threshold raise per undo, for the class and for the region
+    pub boost_per_undo: f32,
+    /// Cap on the combined class + region raise
+    pub max_boost: f32,
+    /// The raise halves after this long without further undos
+    pub half_life_ms: u64,
+    /// Regions are byte buckets of this size
+    pub region_bytes: usize,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self { undo_window_ms: 5_000, boost_per_undo: 0.05, max_boost: 0.15, half_life_ms: 10 * 60_000, region_bytes: 200 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Boost {
+    value: f32,
+    updated_ms: u64,
+}
+
+impl Boost {
+    fn at(&self, now_ms: u64, half_life_ms: u64) -> f32 {
+        self.value * temporal_decay(now_ms.saturating_sub(self.updated_ms), half_life_ms)
+    }
+}
+
+/// Per-user state worth keeping between sessions: class raises decayed to the
+/// save time (regions belong to one document and are not kept)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct LearnedState {
+    pub user: String,
+    pub classes: Vec<(EditType, f32)>,
+}
+
+/// Raises commit thresholds for edit classes and regions the user keeps undoing
+#[derive(Debug, Clone, Default)]
+pub struct AdaptiveThresholds {
+    pub config: AdaptiveConfig,
+    /// (edit type, range, applied at), newest last
+    applied: Vec<(EditType, Range<usize>, u64)>,
+    classes: Vec<(EditType, Boost)>,
+    regions: Vec<(usize, Boost)>,
+}
+
+impl AdaptiveThresholds {
+    pub fn new(config: AdaptiveConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
 
+    /// A system edit was applied over `range`
+    pub fn record_applied(&mut self, edit_type: EditType, range: Range<usize>, now_ms: u64) {
+        const MAX_TRACKED: usize = 32;
+        let window = self.config.undo_window_ms;
+        self.applied.retain(|(_, _, at)| now_ms.saturating_sub(*at) <= window);
+        if self.applied.len() >= MAX_TRACKED {
+            self.applied.remove(0);
+        }
+        self.applied.push((edit_type, range, now_ms));
+    }
 
+    /// The user undid something; if it was a recent system edit, its class and
+    /// region get stricter. Edits applied at the same moment are undone (and
+    /// blamed) together. Returns the class that was penalised.
+    pub fn record_undo(&mut self, now_ms: u64) -> Option<EditType> {
+        let &(edit_type, _, at) = self.applied.last()?;
+        if now_ms.saturating_sub(at) > self.config.undo_window_ms {
+            self.applied.clear();
+            return None;
+        }
+        let split = self.applied.iter().rposition(|(_, _, t)| *t != at).map_or(0, |i| i + 1);
+        let group: Vec<_> = self.applied.drain(split..).collect();
+        let mut classes = Vec::new();
+        let mut regions = Vec::new();
+        for (class, range, _) in &group {
+            let region = self.region(range);
+            if !classes.contains(class) {
+                classes.push(*class);
+                raise(&mut self.classes, *class, &self.config, now_ms);
+            }
+            if !regions.contains(&region) {
+                regions.push(region);
+                raise(&mut self.regions, region, &self.config, now_ms);
+            }
+        }
+        log::debug!("undo of {} system edit(s); thresholds raised for {:?}", group.len(), classes);
+        Some(edit_type)
+    }
+
+    /// Feed a host event; undos are matched against recent system edits
+    pub fn observe(&mut self, event: &CaretEvent) -> Option<EditType> {
+        if event.is_undo() { self.record_undo(event.timestamp_ms) } else { None }
+    }
+
+    /// Current commit-threshold raise for an edit of `edit_type` over `range`
+    pub fn boost(&self, edit_type: EditType, range: &Range<usize>, now_ms: u64) -> f32 {
+        let half_life = self.config.half_life_ms;
+        let total = decayed(&self.classes, edit_type, now_ms, half_life) + decayed(&self.regions, self.region(range), now_ms, half_life);
+        total.min(self.config.max_boost)
+    }
+
+    /// `compute_dynamic_thresholds` plus the learned raise on τ_commit
+    pub fn thresholds(&self, input: &DynamicThresholdsInput, now_ms: u64) -> GateThresholds {
+        let mut thresholds = compute_dynamic_thresholds(input);
+        let boost = self.boost(input.edit_type, &input.range, now_ms);
+        thresholds.commit = (thresholds.commit + boost).min(0.98);
+        thresholds
+    }
+
+    /// Snapshot of the per-class raises for `user`, decayed to `now_ms`
+    pub fn learned(&self, user: &str, now_ms: u64) -> LearnedState {
+        let half_life = self.config.half_life_ms;
+        LearnedState {
+            user: user.to_string(),
+            classes: self.classes.iter().map(|(k, b)| (*k, b.at(now_ms, half_life))).filter(|(_, v)| *v > 0.001).collect(),
+        }
+    }
+
+    /// Restore per-class raises saved by `learned`; they start decaying from `now_ms`
+    pub fn restore(&mut self, state: &LearnedState, now_ms: u64) {
+        self.classes = state
+            .classes
+            .iter()
+            .map(|(k, v)| (*k, Boost { value: clamp01(*v), updated_ms: now_ms }))
+            .collect();
+    }
+
+    fn region(&self, range: &Range<usize>) -> usize {
+        range.start / self.config.region_bytes.max(1)
+    }
+}
+
+fn decayed<K: PartialEq>(boosts: &[(K, Boost)], key: K, now_ms: u64, half_life_ms: u64) -> f32 {
+    boosts.iter().find(|(k, _)| *k == key).map_or(0.0, |(_, b)| b.at(now_ms, half_life_ms))
+}
+
+/// Decay the current raise for `key` to `now_ms`, then add one step
+fn raise<K: PartialEq>(boosts: &mut Vec<(K, Boost)>, key: K, config: &AdaptiveConfig, now_ms: u64) {
+    let value = match boosts.iter().position(|(k, _)| *k == key) {
+        Some(i) => boosts.remove(i).1.at(now_ms, config.half_life_ms),
+        None => 0.0,
+    };
+    boosts.push((key, Boost { value: value + config.boost_per_undo, updated_ms: now_ms }));
+}
+
+/// File holding `user`'s learned state inside `dir`
+pub fn learned_state_path(dir: &Path, user: &str) -> PathBuf {
+    let name: String = user.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    dir.join(format!("adaptive-{}.json", name))
+}
+
+pub fn save_learned_state(dir: &Path, state: &LearnedState) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let json = serde_json::to_vec_pretty(state).map_err(io::Error::other)?;
+    fs::write(learned_state_path(dir, &state.user), json)
+}
+
+/// `Ok(None)` when nothing was saved for `user` yet
+pub fn load_learned_state(dir: &Path, user: &str) -> io::Result<Option<LearnedState>> {
+    match fs::read(learned_state_path(dir, user)) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
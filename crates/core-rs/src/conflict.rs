@@ -0,0 +1,232 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  C O N F L I C T   R E S O L V E R  ░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Deterministically resolves overlapping proposals.          ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Merge and filter overlapping edits from Noise, LM,
+           Context and Tone (port of engines/conflictResolver.ts)
+  • WHY  ▸ Prevent conflicting suggestions and thrash
+  • HOW  ▸ Sort by source priority then policy; drop overlaps with
+           a reason, or fold them into the winner when composable
+*/
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::diff::TextEdit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProposalSource {
+    Noise,
+    Lm,
+    Context,
+    Tone,
+}
+
+impl ProposalSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProposalSource::Noise => "noise",
+            ProposalSource::Lm => "lm",
+            ProposalSource::Context => "context",
+            ProposalSource::Tone => "tone",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub source: ProposalSource,
+    /// Byte range of the text passed to `resolve`
+    pub edit: TextEdit,
+    pub confidence: f32,
+    pub timestamp_ms: u64,
+}
+
+/// How overlapping proposals from sources of equal priority are ranked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Longer span, then higher confidence, then newer (the TS resolver)
+    #[default]
+    PreferLonger,
+    /// Higher confidence, then longer span, then newer
+    PreferHigherConfidence,
+    /// As `PreferLonger`, but a loser inside the winner's range is folded into
+    /// the winner when the winner leaves that text untouched
+    Compose,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolverConfig {
+    /// Earlier sources win overlaps; unlisted sources rank last
+    pub precedence: Vec<ProposalSource>,
+    pub policy: MergePolicy,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            precedence: vec![ProposalSource::Noise, ProposalSource::Lm, ProposalSource::Context, ProposalSource::Tone],
+            policy: MergePolicy::default(),
+        }
+    }
+}
+
+/// Why a proposal lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// Range is reversed, out of bounds or not on char boundaries
+    InvalidRange,
+    /// Overlapping winner comes from a higher-priority source
+    Priority,
+    /// Overlapping winner spans more text
+    Shorter,
+    /// Overlapping winner is more confident
+    LowerConfidence,
+    /// Overlapping winner was proposed later
+    Older,
+    /// Everything tied; the earlier start wins
+    Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dropped {
+    pub proposal: Proposal,
+    pub reason: DropReason,
+    /// The accepted proposal it overlapped (as originally proposed)
+    pub winner: Option<Proposal>,
+}
+
+impl fmt::Display for Dropped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |p: &Proposal| format!("{} {}..{}", p.source.name(), p.edit.range.start, p.edit.range.end);
+        write!(f, "{} dropped: ", describe(&self.proposal))?;
+        let Some(winner) = &self.winner else {
+            return write!(f, "invalid range");
+        };
+        let why = match self.reason {
+            DropReason::InvalidRange => "invalid range",
+            DropReason::Priority => "higher-priority source",
+            DropReason::Shorter => "longer span",
+            DropReason::LowerConfidence => "higher confidence",
+            DropReason::Older => "newer proposal",
+            DropReason::Position => "earlier start",
+        };
+        write!(f, "overlaps {} ({})", describe(winner), why)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Resolution {
+    /// Non-overlapping, ascending by range; apply with `TextEdit::apply_all`
+    pub accepted: Vec<Proposal>,
+    pub dropped: Vec<Dropped>,
+    /// Losers folded into an accepted proposal (`MergePolicy::Compose`)
+    pub composed: Vec<Proposal>,
+}
+
+impl Resolution {
+    pub fn edits(&self) -> Vec<TextEdit> {
+        self.accepted.iter().map(|p| p.edit.clone()).collect()
+    }
+}
+
+#[derive(Default)]
+pub struct ConflictResolver {
+    pub config: ResolverConfig,
+}
+
+impl ConflictResolver {
+    pub fn new(config: ResolverConfig) -> Self {
+        Self { config }
+    }
+
+    /// Resolve proposals made against `text`
+    pub fn resolve(&self, text: &str, proposals: Vec<Proposal>) -> Resolution {
+        let mut out = Resolution::default();
+        let (mut valid, invalid): (Vec<Proposal>, Vec<Proposal>) =
+            proposals.into_iter().partition(|p| text.get(p.edit.range.clone()).is_some());
+        out.dropped.extend(invalid.into_iter().map(|proposal| Dropped { proposal, reason: DropReason::InvalidRange, winner: None }));
+
+        valid.sort_by(|a, b| self.rank(a, b).0);
+        // (as accepted, as originally proposed)
+        let mut accepted: Vec<(Proposal, Proposal)> = Vec::new();
+        for p in valid {
+            let Some(i) = accepted.iter().position(|(_, w)| overlaps(&w.edit, &p.edit)) else {
+                accepted.push((p.clone(), p));
+                continue;
+            };
+            if self.config.policy == MergePolicy::Compose {
+                if let Some(edit) = compose(text, &accepted[i].0.edit, &p.edit) {
+                    let merged = &mut accepted[i].0;
+                    merged.edit = edit;
+                    merged.confidence = merged.confidence.min(p.confidence);
+                    out.composed.push(p);
+                    continue;
+                }
+            }
+            let winner = accepted[i].1.clone();
+            let reason = self.rank(&winner, &p).1;
+            out.dropped.push(Dropped { proposal: p, reason, winner: Some(winner) });
+        }
+        out.accepted = accepted.into_iter().map(|(p, _)| p).collect();
+        out.accepted.sort_by_key(|p| (p.edit.range.start, p.edit.range.end));
+        out
+    }
+
+    /// Order of `a` against `b` (Less = `a` wins) and the deciding criterion
+    fn rank(&self, a: &Proposal, b: &Proposal) -> (Ordering, DropReason) {
+        let priority = |p: &Proposal| {
+            self.config.precedence.iter().position(|s| *s == p.source).unwrap_or(self.config.precedence.len())
+        };
+        let longer = b.edit.range.len().cmp(&a.edit.range.len());
+        let confident = b.confidence.total_cmp(&a.confidence);
+        let (first, second) = match self.config.policy {
+            MergePolicy::PreferHigherConfidence => ((confident, DropReason::LowerConfidence), (longer, DropReason::Shorter)),
+            _ => ((longer, DropReason::Shorter), (confident, DropReason::LowerConfidence)),
+        };
+        [
+            (priority(a).cmp(&priority(b)), DropReason::Priority),
+            first,
+            second,
+            (b.timestamp_ms.cmp(&a.timestamp_ms), DropReason::Older),
+            ((a.edit.range.start, a.edit.range.end).cmp(&(b.edit.range.start, b.edit.range.end)), DropReason::Position),
+        ]
+        .into_iter()
+        .find(|(o, _)| o.is_ne())
+        .unwrap_or((Ordering::Equal, DropReason::Position))
+    }
+}
+
+/// Shortcut for `ConflictResolver::default().resolve`
+pub fn resolve_conflicts(text: &str, proposals: Vec<Proposal>) -> Resolution {
+    ConflictResolver::default().resolve(text, proposals)
+}
+
+/// Half-open ranges; a zero-width insertion only conflicts strictly inside a span
+fn overlaps(a: &TextEdit, b: &TextEdit) -> bool {
+    (a.range.start < b.range.end && b.range.start < a.range.end) || (a.range == b.range)
+}
+
+/// Apply `inner` on top of `outer`'s replacement when `inner` lies inside
+/// `outer`'s range and `outer` kept that text (anchored at either end)
+fn compose(text: &str, outer: &TextEdit, inner: &TextEdit) -> Option<TextEdit> {
+    if inner.range.start < outer.range.start || inner.range.end > outer.range.end {
+        return None;
+    }
+    let original = &text[inner.range.clone()];
+    let replacement = &outer.replacement;
+    let prefix = &text[outer.range.start..inner.range.start];
+    let suffix = &text[inner.range.end..outer.range.end];
+    let at = if replacement.starts_with(prefix) && replacement[prefix.len()..].starts_with(original) {
+        prefix.len()
+    } else if replacement.ends_with(suffix) && replacement[..replacement.len() - suffix.len()].ends_with(original) {
+        replacement.len() - suffix.len() - original.len()
+    } else {
+        return None;
+    };
+    let mut composed = replacement.clone();
+    composed.replace_range(at..at + original.len(), &inner.replacement);
+    Some(TextEdit::new(outer.range.clone(), composed))
+}
@@ -0,0 +1,17 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  D E F A U L T   T H R E S H O L D S  ░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Tuning constants shared by the workers and the gate.       ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Limits more than one module reads (port of
+           config/defaultThresholds.ts)
+  • WHY  ▸ The confidence gate must not depend on a worker just to
+           learn the sweep window
+  • HOW  ▸ Plain constants; per-instance settings stay with their
+           owners (caret_monitor::Thresholds, ConfidenceConfig)
+*/
+
+/// Bytes behind the caret a noise rule may look at; the gate scores edits
+/// this far back as having no caret proximity left
+pub const MAX_SWEEP_WINDOW: usize = 80;
@@ -12,11 +12,20 @@
   ╚══════════════════════════════════════════════════════════╝
   • WHAT ▸ No edits at or after the caret
   • WHY  ▸ REQ-IME-CARETSAFE
-  • HOW  ▸ See linked contracts and guides in docs
+  • HOW  ▸ Myers diff (patience/histogram anchors optional) over grapheme clusters
 */
 
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::offsets::{self, OffsetUnit};
+
 /// Represents a text difference operation
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DiffOp {
     /// Keep text unchanged
     Keep(String),
@@ -28,23 +37,582 @@ pub enum DiffOp {
     Replace(String, String),
 }
 
+impl DiffOp {
+    /// Text this operation consumes from the original
+    pub fn original_text(&self) -> &str {
+        match self {
+            DiffOp::Keep(s) | DiffOp::Delete(s) | DiffOp::Replace(s, _) => s,
+            DiffOp::Insert(_) => "",
+        }
+    }
+
+    /// Text this operation produces in the modified output
+    pub fn modified_text(&self) -> &str {
+        match self {
+            DiffOp::Keep(s) | DiffOp::Insert(s) | DiffOp::Replace(_, s) => s,
+            DiffOp::Delete(_) => "",
+        }
+    }
+
+    /// Whether this operation changes the text
+    pub fn is_edit(&self) -> bool {
+        !matches!(self, DiffOp::Keep(_))
+    }
+}
+
+/// Offset-addressed edit: replace `range` of the original with `replacement`
+///
+/// Ranges are UTF-8 byte offsets unless converted with `TextEdit::to_unit`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Create an edit over a byte range
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self { range, replacement: replacement.into() }
+    }
+
+    /// Convert the range of an edit against `text` from bytes into `unit`
+    pub fn to_unit(&self, text: &str, unit: OffsetUnit) -> Option<TextEdit> {
+        let range = offsets::convert_range(text, self.range.clone(), OffsetUnit::Utf8, unit)?;
+        Some(TextEdit { range, replacement: self.replacement.clone() })
+    }
+
+    /// Convert an edit whose range is counted in `unit` back into bytes
+    pub fn from_unit(text: &str, range: Range<usize>, unit: OffsetUnit, replacement: impl Into<String>) -> Option<TextEdit> {
+        let range = offsets::convert_range(text, range, unit, OffsetUnit::Utf8)?;
+        Some(TextEdit { range, replacement: replacement.into() })
+    }
+
+    /// Apply non-overlapping byte-range edits (all relative to `text`)
+    pub fn apply_all(text: &str, edits: &[TextEdit]) -> Option<String> {
+        let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+        sorted.sort_by_key(|e| (e.range.start, e.range.end));
+        let mut out = String::with_capacity(text.len());
+        let mut cursor = 0usize;
+        for edit in sorted {
+            let Range { start, end } = edit.range;
+            if start < cursor || end < start || text.get(start..end).is_none() {
+                return None;
+            }
+            out.push_str(&text[cursor..start]);
+            out.push_str(&edit.replacement);
+            cursor = end;
+        }
+        out.push_str(&text[cursor..]);
+        Some(out)
+    }
+}
+
+/// Unit the diff is computed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Granularity {
+    /// Unicode scalar values
+    Char,
+    /// Extended grapheme clusters; never splits emoji or combining sequences
+    #[default]
+    Grapheme,
+}
+
+/// Strategy used to align the two sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffAlgorithm {
+    /// Minimal edit script (Myers O(ND))
+    #[default]
+    Myers,
+    /// Anchor on tokens unique to both sides, Myers between anchors
+    Patience,
+    /// Anchor on the rarest shared token, Myers when none remain
+    Histogram,
+}
+
+/// Options for `DiffCalculator::calculate_diff_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiffOptions {
+    pub algorithm: DiffAlgorithm,
+    pub granularity: Granularity,
+}
+
+/// Error returned when operations do not match the text they are applied to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffError {
+    /// Text at `offset` differs from what the operation expected to consume
+    Mismatch { offset: usize, expected: String },
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::Mismatch { offset, expected } => {
+                write!(f, "diff mismatch at byte {}: expected {:?}", offset, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
 /// Text difference calculator
 pub struct DiffCalculator;
 
 impl DiffCalculator {
-    /// Calculate differences between two text strings
+    /// Calculate differences between two text strings (Myers, grapheme level)
     pub fn calculate_diff(original: &str, modified: &str) -> Vec<DiffOp> {
-        // Stub implementation - returns simple replacement
-        if original != modified {
-            vec![DiffOp::Replace(original.to_string(), modified.to_string())]
-        } else {
-            vec![DiffOp::Keep(original.to_string())]
+        Self::calculate_diff_with(original, modified, &DiffOptions::default())
+    }
+
+    /// Calculate differences with an explicit algorithm and granularity
+    pub fn calculate_diff_with(original: &str, modified: &str, options: &DiffOptions) -> Vec<DiffOp> {
+        let a = tokenize(original, options.granularity);
+        let b = tokenize(modified, options.granularity);
+
+        // Common prefix/suffix never need alignment
+        let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+        let suffix = a[prefix..]
+            .iter()
+            .rev()
+            .zip(b[prefix..].iter().rev())
+            .take_while(|(x, y)| x == y)
+            .count();
+
+        let mut edits: Vec<Edit> = a[..prefix].iter().map(|t| Edit::Equal(t)).collect();
+        let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+        match options.algorithm {
+            DiffAlgorithm::Myers => myers(mid_a, mid_b, &mut edits),
+            DiffAlgorithm::Patience => patience(mid_a, mid_b, &mut edits),
+            DiffAlgorithm::Histogram => histogram(mid_a, mid_b, &mut edits),
         }
+        edits.extend(a[a.len() - suffix..].iter().map(|t| Edit::Equal(t)));
+
+        coalesce(&edits)
+    }
+
+    /// Convert operations into byte-range edits against the original text
+    pub fn to_edits(operations: &[DiffOp]) -> Vec<TextEdit> {
+        let mut edits = Vec::new();
+        let mut offset = 0usize;
+        for op in operations {
+            let len = op.original_text().len();
+            if op.is_edit() {
+                edits.push(TextEdit::new(offset..offset + len, op.modified_text()));
+            }
+            offset += len;
+        }
+        edits
     }
 
     /// Apply diff operations to text
-    pub fn apply_diff(text: &str, _operations: &[DiffOp]) -> String {
-        // Stub implementation - returns original text
-        text.to_string()
+    ///
+    /// Text not covered by the operations is kept. If the operations do not
+    /// match `text`, the original is returned unchanged; use
+    /// `try_apply_diff` to observe the mismatch.
+    pub fn apply_diff(text: &str, operations: &[DiffOp]) -> String {
+        Self::try_apply_diff(text, operations).unwrap_or_else(|_| text.to_string())
+    }
+
+    /// Apply diff operations, failing if any consumed text does not match
+    pub fn try_apply_diff(text: &str, operations: &[DiffOp]) -> Result<String, DiffError> {
+        let mut out = String::with_capacity(text.len());
+        let mut cursor = 0usize;
+        for op in operations {
+            let expected = op.original_text();
+            if !text[cursor..].starts_with(expected) {
+                return Err(DiffError::Mismatch { offset: cursor, expected: expected.to_string() });
+            }
+            cursor += expected.len();
+            out.push_str(op.modified_text());
+        }
+        out.push_str(&text[cursor..]);
+        Ok(out)
+    }
+}
+
+// ────────────────────────────────────────────────────────────────
+// Caret-safe enforcement
+// ────────────────────────────────────────────────────────────────
+
+/// Why an edit was removed by `CaretSafeDiff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipReason {
+    /// The edit starts at or after the caret
+    AtOrAfterCaret,
+    /// Tail of a replacement that spans the caret; its head before the caret was kept
+    StraddlesCaret,
+}
+
+/// An edit (or the part of one) that was dropped to keep the caret safe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClippedEdit {
+    /// Byte offset of the dropped edit in the original text
+    pub offset: usize,
+    pub op: DiffOp,
+    pub reason: ClipReason,
+}
+
+/// Operations guaranteed not to touch text at or after the caret
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CaretSafeResult {
+    pub operations: Vec<DiffOp>,
+    pub clipped: Vec<ClippedEdit>,
+}
+
+impl CaretSafeResult {
+    /// Whether any edit had to be dropped
+    pub fn was_clipped(&self) -> bool {
+        !self.clipped.is_empty()
+    }
+
+    /// Surviving operations as byte-range edits
+    pub fn edits(&self) -> Vec<TextEdit> {
+        DiffCalculator::to_edits(&self.operations)
+    }
+}
+
+/// Caret-safe layer over `DiffCalculator`
+///
+/// Deletions and replacements must end at or before the caret; insertions
+/// must land strictly before it. Deletions and replacements that straddle the
+/// caret are split and only the part before it survives. A straddling
+/// replacement keeps as much new text as the old text it covers before the
+/// caret (counted in graphemes), unless its tail is unchanged anyway.
+#[derive(Debug, Clone, Copy)]
+pub struct CaretSafeDiff {
+    /// Caret position as a byte offset into the original text
+    pub caret: usize,
+}
+
+impl CaretSafeDiff {
+    /// Create a guard for the given caret byte offset
+    pub fn new(caret: usize) -> Self {
+        Self { caret }
+    }
+
+    /// Diff two strings and clip the result at the caret
+    pub fn calculate(&self, original: &str, modified: &str) -> CaretSafeResult {
+        self.enforce(original, &DiffCalculator::calculate_diff(original, modified))
+    }
+
+    /// Diff with explicit options and clip the result at the caret
+    pub fn calculate_with(&self, original: &str, modified: &str, options: &DiffOptions) -> CaretSafeResult {
+        self.enforce(original, &DiffCalculator::calculate_diff_with(original, modified, options))
+    }
+
+    /// Clip existing operations against `original`; operations that do not
+    /// span `original` exactly yield an empty result
+    pub fn enforce(&self, original: &str, operations: &[DiffOp]) -> CaretSafeResult {
+        let mut result = CaretSafeResult::default();
+        if operations.iter().map(|op| op.original_text().len()).sum::<usize>() != original.len() {
+            return result;
+        }
+        let caret = floor_grapheme_boundary(original, self.caret.min(original.len()));
+        let mut offset = 0usize;
+
+        for op in operations {
+            let len = op.original_text().len();
+            let end = offset + len;
+            let safe = match op {
+                DiffOp::Keep(_) => Some(op.clone()),
+                _ if len == 0 && offset < caret => Some(op.clone()),
+                _ if len > 0 && end <= caret => Some(op.clone()),
+                // Ops whose text differs from `original` may not split at the caret; clip them whole
+                DiffOp::Delete(s) if offset < caret && s.is_char_boundary(caret - offset) => {
+                    let (before, after) = s.split_at(caret - offset);
+                    result.clipped.push(ClippedEdit {
+                        offset: caret,
+                        op: DiffOp::Delete(after.to_string()),
+                        reason: ClipReason::AtOrAfterCaret,
+                    });
+                    push_op(&mut result.operations, DiffOp::Delete(before.to_string()));
+                    Some(DiffOp::Keep(after.to_string()))
+                }
+                DiffOp::Replace(old, new) if offset < caret && old.is_char_boundary(caret - offset) => {
+                    let (old_before, old_after) = old.split_at(caret - offset);
+                    let new_before = new.strip_suffix(old_after).unwrap_or_else(|| {
+                        let graphemes = old_before.graphemes(true).count();
+                        &new[..new.grapheme_indices(true).nth(graphemes).map_or(new.len(), |(i, _)| i)]
+                    });
+                    let new_after = &new[new_before.len()..];
+                    if new_after != old_after {
+                        let op = if new_after.is_empty() {
+                            DiffOp::Delete(old_after.to_string())
+                        } else {
+                            DiffOp::Replace(old_after.to_string(), new_after.to_string())
+                        };
+                        result.clipped.push(ClippedEdit { offset: caret, op, reason: ClipReason::StraddlesCaret });
+                    }
+                    let head = match new_before {
+                        "" => DiffOp::Delete(old_before.to_string()),
+                        same if same == old_before => DiffOp::Keep(same.to_string()),
+                        changed => DiffOp::Replace(old_before.to_string(), changed.to_string()),
+                    };
+                    push_op(&mut result.operations, head);
+                    Some(DiffOp::Keep(old_after.to_string()))
+                }
+                _ => {
+                    result.clipped.push(ClippedEdit { offset, op: op.clone(), reason: ClipReason::AtOrAfterCaret });
+                    (len > 0).then(|| DiffOp::Keep(op.original_text().to_string()))
+                }
+            };
+            if let Some(op) = safe {
+                push_op(&mut result.operations, op);
+            }
+            offset = end;
+        }
+        result
+    }
+}
+
+/// Append `op`, merging adjacent runs of the same kind
+fn push_op(ops: &mut Vec<DiffOp>, op: DiffOp) {
+    match (ops.last_mut(), op) {
+        (Some(DiffOp::Keep(prev)), DiffOp::Keep(next)) => prev.push_str(&next),
+        (Some(DiffOp::Delete(prev)), DiffOp::Delete(next)) => prev.push_str(&next),
+        (Some(DiffOp::Insert(prev)), DiffOp::Insert(next)) => prev.push_str(&next),
+        (_, op) => ops.push(op),
+    }
+}
+
+/// Largest grapheme boundary in `text` that is `<= index`
+fn floor_grapheme_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
     }
-}
\ No newline at end of file
+    text.grapheme_indices(true)
+        .map(|(i, _)| i)
+        .take_while(|&i| i <= index)
+        .last()
+        .unwrap_or(0)
+}
+
+// ────────────────────────────────────────────────────────────────
+// Token-level edit script
+// ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy)]
+enum Edit<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+fn tokenize(text: &str, granularity: Granularity) -> Vec<&str> {
+    match granularity {
+        Granularity::Grapheme => text.graphemes(true).collect(),
+        Granularity::Char => text.char_indices().map(|(i, c)| &text[i..i + c.len_utf8()]).collect(),
+    }
+}
+
+/// Merge per-token edits into runs; a change hunk with both deletions and
+/// insertions becomes a single `Replace`.
+fn coalesce(edits: &[Edit]) -> Vec<DiffOp> {
+    let mut ops = Vec::new();
+    let mut keep = String::new();
+    let mut del = String::new();
+    let mut ins = String::new();
+
+    fn flush_change(ops: &mut Vec<DiffOp>, del: &mut String, ins: &mut String) {
+        match (del.is_empty(), ins.is_empty()) {
+            (true, true) => {}
+            (false, true) => ops.push(DiffOp::Delete(std::mem::take(del))),
+            (true, false) => ops.push(DiffOp::Insert(std::mem::take(ins))),
+            (false, false) => ops.push(DiffOp::Replace(std::mem::take(del), std::mem::take(ins))),
+        }
+    }
+
+    for edit in edits {
+        match edit {
+            Edit::Equal(t) => {
+                flush_change(&mut ops, &mut del, &mut ins);
+                keep.push_str(t);
+            }
+            Edit::Delete(t) | Edit::Insert(t) => {
+                if !keep.is_empty() {
+                    ops.push(DiffOp::Keep(std::mem::take(&mut keep)));
+                }
+                if matches!(edit, Edit::Delete(_)) { del.push_str(t) } else { ins.push_str(t) }
+            }
+        }
+    }
+    flush_change(&mut ops, &mut del, &mut ins);
+    if !keep.is_empty() {
+        ops.push(DiffOp::Keep(keep));
+    }
+    ops
+}
+
+/// Classic Myers greedy diff with V-vector trace and backtracking. Each step
+/// keeps only the diagonals `-d..=d` it can reach, so the trace is O(D²).
+fn myers<'a>(a: &[&'a str], b: &[&'a str], out: &mut Vec<Edit<'a>>) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    if n == 0 || m == 0 {
+        out.extend(a.iter().map(|t| Edit::Delete(t)));
+        out.extend(b.iter().map(|t| Edit::Insert(t)));
+        return;
+    }
+
+    let max = (n + m) as usize;
+    let offset = max as isize;
+    let mut v = vec![0isize; 2 * max + 2];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { v[idx + 1] } else { v[idx - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // Walk the trace backwards to recover the path
+    let mut script = Vec::with_capacity(max);
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            // `v` holds diagonal k at index k + d
+            let k = x - y;
+            let idx = (k + d) as usize;
+            let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { k + 1 } else { k - 1 };
+            let prev_x = v[(prev_k + d) as usize];
+            (prev_x, prev_x - prev_k)
+        };
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            script.push(Edit::Equal(a[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                script.push(Edit::Insert(b[prev_y as usize]));
+            } else {
+                script.push(Edit::Delete(a[prev_x as usize]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    script.reverse();
+    out.extend(script);
+}
+
+/// Patience diff: align tokens that occur exactly once on both sides along
+/// their longest increasing subsequence, then diff the gaps.
+fn patience<'a>(a: &[&'a str], b: &[&'a str], out: &mut Vec<Edit<'a>>) {
+    let mut counts: HashMap<&str, (usize, usize, usize, usize)> = HashMap::new();
+    for (i, t) in a.iter().enumerate() {
+        let e = counts.entry(t).or_insert((0, 0, i, 0));
+        e.0 += 1;
+    }
+    for (j, t) in b.iter().enumerate() {
+        if let Some(e) = counts.get_mut(t) {
+            e.1 += 1;
+            e.3 = j;
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = counts
+        .values()
+        .filter(|(ca, cb, _, _)| *ca == 1 && *cb == 1)
+        .map(|&(_, _, i, j)| (i, j))
+        .collect();
+    if pairs.is_empty() {
+        myers(a, b, out);
+        return;
+    }
+    pairs.sort_unstable();
+
+    let (mut ai, mut bj) = (0, 0);
+    for (i, j) in longest_increasing(&pairs) {
+        myers(&a[ai..i], &b[bj..j], out);
+        out.push(Edit::Equal(a[i]));
+        ai = i + 1;
+        bj = j + 1;
+    }
+    myers(&a[ai..], &b[bj..], out);
+}
+
+/// Longest subsequence of `pairs` (sorted by `.0`) increasing in `.1`.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (idx, &(_, j)) in pairs.iter().enumerate() {
+        let pos = tails.partition_point(|&t| pairs[t].1 < j);
+        if pos > 0 {
+            prev[idx] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(idx);
+        } else {
+            tails[pos] = idx;
+        }
+    }
+    let mut chain = Vec::with_capacity(tails.len());
+    let mut cur = tails.last().copied();
+    while let Some(idx) = cur {
+        chain.push(pairs[idx]);
+        cur = prev[idx];
+    }
+    chain.reverse();
+    chain
+}
+
+/// Recursion depth past which histogram hands the rest of the span to myers.
+const MAX_HISTOGRAM_DEPTH: usize = 64;
+
+/// Histogram diff: anchor on the shared token with the fewest occurrences in
+/// `a`, grow the match in both directions, and recurse on either side.
+fn histogram<'a>(a: &[&'a str], b: &[&'a str], out: &mut Vec<Edit<'a>>) {
+    histogram_at(a, b, out, 0);
+}
+
+fn histogram_at<'a>(a: &[&'a str], b: &[&'a str], out: &mut Vec<Edit<'a>>, depth: usize) {
+    if a.is_empty() || b.is_empty() || depth >= MAX_HISTOGRAM_DEPTH {
+        myers(a, b, out);
+        return;
+    }
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    for t in a {
+        *occurrences.entry(t).or_insert(0) += 1;
+    }
+    let anchor = b
+        .iter()
+        .enumerate()
+        .filter_map(|(j, t)| occurrences.get(t).map(|&c| (c, j)))
+        .min_by_key(|&(c, j)| (c, j));
+    let Some((_, j)) = anchor else {
+        myers(a, b, out);
+        return;
+    };
+    let i = a.iter().position(|t| *t == b[j]).unwrap_or(0);
+
+    let (mut start_a, mut start_b) = (i, j);
+    while start_a > 0 && start_b > 0 && a[start_a - 1] == b[start_b - 1] {
+        start_a -= 1;
+        start_b -= 1;
+    }
+    let (mut end_a, mut end_b) = (i + 1, j + 1);
+    while end_a < a.len() && end_b < b.len() && a[end_a] == b[end_b] {
+        end_a += 1;
+        end_b += 1;
+    }
+
+    histogram_at(&a[..start_a], &b[..start_b], out, depth + 1);
+    out.extend(a[start_a..end_a].iter().map(|t| Edit::Equal(t)));
+    histogram_at(&a[end_a..], &b[end_b..], out, depth + 1);
+}
+
@@ -16,24 +16,357 @@
   • HOW  ▸ Drives LM + rule passes; emits diffs for host injectors
 */
 
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::active_region::ActiveRegionPolicy;
+use crate::caret_monitor::{CaretEvent, CaretMonitor, CaretPrimaryState, CaretSnapshot, DeviceTier, Thresholds};
+use crate::clock::{self, Clock};
+use crate::confidence::{
+    apply_thresholds, score_proposal, AdaptiveThresholds, ConfidenceConfig, DynamicThresholdsInput, EditType,
+    GateDecision, GateThresholds,
+};
+use crate::diff::{CaretSafeDiff, ClippedEdit, TextEdit};
+use crate::language_detection::{self, Language, LanguageDetector};
+use crate::merge::fingerprint;
+use crate::offsets::floor_char_boundary;
+use crate::typo_model::{self, KeyboardLayout, TypoModel};
+use crate::workers::tone::{self, ToneSettings, ToneWorker};
+use serde::{Deserialize, Serialize};
+
 pub struct EngineConfig {
     pub short_pause_ms: u64,
     pub long_pause_ms: u64,
+    /// Words behind the caret considered for correction
+    pub target_words: usize,
+    /// Base confidence an edit needs before it is emitted
+    pub base_threshold: f32,
+    /// Layout the typo model scores against (QWERTY unless configured)
+    pub keyboard_layout: KeyboardLayout,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        let thresholds = Thresholds::default();
+        Self {
+            short_pause_ms: thresholds.short_pause_ms,
+            long_pause_ms: thresholds.long_pause_ms,
+            target_words: 20,
+            base_threshold: 0.8,
+            keyboard_layout: KeyboardLayout::Qwerty,
+        }
+    }
+}
+
+/// Corrected text for an active region, with the corrector's confidence
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    pub text: String,
+    pub confidence: f32,
+}
+
+/// Pluggable correction pass (rules, LM, or a host-provided callback)
+pub trait Corrector {
+    fn correct(&mut self, region: &str, language: &Language) -> Option<Correction>;
+
+    /// Layout for correctors that score keyboard typos; ignored by default
+    fn set_keyboard_layout(&mut self, _layout: KeyboardLayout) {}
+}
+
+impl<F> Corrector for F
+where
+    F: FnMut(&str, &Language) -> Option<Correction>,
+{
+    fn correct(&mut self, region: &str, language: &Language) -> Option<Correction> {
+        self(region, language)
+    }
+}
+
+/// Corrector that never proposes anything
+pub struct NoopCorrector;
+
+impl Corrector for NoopCorrector {
+    fn correct(&mut self, _region: &str, _language: &Language) -> Option<Correction> {
+        None
+    }
+}
+
+/// Why a tick produced no edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SkipReason {
+    /// The user is not in a pause (typing, IME, paste, selection…)
+    NotPaused,
+    /// Same text and caret as the last processed tick
+    Unchanged,
+    /// Nothing behind the caret to correct
+    EmptyRegion,
+    /// The corrector declined or returned the region unchanged
+    NoCorrection,
+    /// Every candidate edit was clipped or below the confidence threshold
+    Gated,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickDiagnostics {
+    pub state: CaretPrimaryState,
+    /// Active region as a byte range of the document
+    pub region: Option<Range<usize>>,
+    pub language: Option<(Language, f32)>,
+    pub confidence: Option<f32>,
+    /// Edits dropped because they touched the caret
+    pub clipped: Vec<ClippedEdit>,
+    /// Edits dropped by the confidence gate
+    pub gated: usize,
+    pub skipped: Option<SkipReason>,
+}
+
+/// Result of one engine tick: caret-safe byte-range edits plus diagnostics
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickOutput {
+    pub edits: Vec<TextEdit>,
+    pub diagnostics: TickDiagnostics,
 }
 
 pub struct Engine {
     config: EngineConfig,
+    monitor: CaretMonitor,
+    region_policy: ActiveRegionPolicy,
+    detector: LanguageDetector,
+    confidence: ConfidenceConfig,
+    /// Learns from undos of emitted edits
+    adaptive: AdaptiveThresholds,
+    corrector: Box<dyn Corrector>,
+    clock: Arc<dyn Clock>,
+    /// Fingerprint of the last processed text and caret, and whether it was a long pause
+    last_processed: Option<(u64, bool)>,
+    /// Engine-specific tone; `None` follows the process-wide setting
+    tone: Option<ToneSettings>,
+    tone_worker: ToneWorker,
+    /// Global layout report last adopted (see `typo_model::global_layout_report`)
+    layout_report: u64,
 }
 
 impl Engine {
     pub fn new(config: EngineConfig) -> Self {
-        Self { config }
+        Self::with_corrector(config, Box::new(NoopCorrector))
     }
 
-    pub fn tick(&mut self, _text: &str, _caret: usize) {
-        // ⟢ Placeholder: integrate LM/rules scheduling here
+    pub fn with_corrector(config: EngineConfig, mut corrector: Box<dyn Corrector>) -> Self {
+        corrector.set_keyboard_layout(config.keyboard_layout);
+        let thresholds = Thresholds {
+            short_pause_ms: config.short_pause_ms,
+            long_pause_ms: config.long_pause_ms,
+            ..Thresholds::default()
+        };
+        Self {
+            monitor: CaretMonitor::new(thresholds, DeviceTier::Native),
+            region_policy: ActiveRegionPolicy::new(config.target_words),
+            detector: LanguageDetector::new(),
+            confidence: ConfidenceConfig { base_threshold: config.base_threshold },
+            adaptive: AdaptiveThresholds::default(),
+            corrector,
+            clock: clock::system(),
+            last_processed: None,
+            tone: None,
+            tone_worker: ToneWorker::default(),
+            layout_report: typo_model::global_layout_report().0,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    pub fn monitor(&self) -> &CaretMonitor {
+        &self.monitor
+    }
+
+    /// Replace the time source (e.g. a `ManualClock` for tests and replay)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Current time source
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
     }
-}
 
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+
+    pub fn set_corrector(&mut self, mut corrector: Box<dyn Corrector>) {
+        corrector.set_keyboard_layout(self.config.keyboard_layout);
+        self.corrector = corrector;
+    }
+
+    /// Typo model for the configured keyboard layout
+    pub fn typo_model(&self) -> TypoModel {
+        TypoModel::new(self.config.keyboard_layout)
+    }
+
+    /// Switch layouts (e.g. when the host reports a keyboard change)
+    pub fn set_keyboard_layout(&mut self, layout: KeyboardLayout) {
+        self.config.keyboard_layout = layout;
+        self.corrector.set_keyboard_layout(layout);
+        self.last_processed = None;
+    }
+
+    /// Adopt a layout the host reported since this engine last looked
+    fn follow_host_layout(&mut self) {
+        let (report, layout) = typo_model::global_layout_report();
+        if report != self.layout_report {
+            self.layout_report = report;
+            self.set_keyboard_layout(layout);
+        }
+    }
+
+    /// Tone settings in effect: this engine's override, else the global setting
+    pub fn tone(&self) -> ToneSettings {
+        self.tone.unwrap_or_else(tone::global_tone)
+    }
+
+    /// Override the tone for this engine; `None` reverts to the global setting
+    pub fn set_tone(&mut self, settings: Option<ToneSettings>) {
+        self.tone = settings;
+    }
+
+    pub fn adaptive(&self) -> &AdaptiveThresholds {
+        &self.adaptive
+    }
+
+    /// E.g. to restore a user's learned state
+    pub fn adaptive_mut(&mut self) -> &mut AdaptiveThresholds {
+        &mut self.adaptive
+    }
+
+    /// Monitor snapshots emitted since the last drain
+    pub fn drain_snapshots(&mut self) -> Vec<CaretSnapshot> {
+        self.monitor.drain_snapshots()
+    }
+
+    /// Feed a host caret/input event
+    pub fn observe(&mut self, event: CaretEvent) -> bool {
+        self.adaptive.observe(&event);
+        self.monitor.update(event)
+    }
+
+    /// Run pause → region → correct → diff for `text` with the caret at byte `caret`;
+    /// a long pause over English text adds tone rewrites toward `tone()`
+    pub fn tick(&mut self, text: &str, caret: usize) -> TickOutput {
+        let now_ms = self.clock.now_ms();
+        self.tick_at(text, caret, now_ms)
+    }
+
+    /// `tick` at an explicit timestamp instead of the engine clock
+    pub fn tick_at(&mut self, text: &str, caret: usize, now_ms: u64) -> TickOutput {
+        self.follow_host_layout();
+        self.monitor.flush(now_ms);
+        let state = self.monitor.get_state().primary;
+        let mut diagnostics = TickDiagnostics {
+            state,
+            region: None,
+            language: None,
+            confidence: None,
+            clipped: Vec::new(),
+            gated: 0,
+            skipped: None,
+        };
+        if !matches!(state, CaretPrimaryState::ShortPause | CaretPrimaryState::LongPause) {
+            return skipped(diagnostics, SkipReason::NotPaused);
+        }
+        let caret = floor_char_boundary(text, caret);
+        // A long pause over text already seen at a short one still owes the tone pass
+        let long = state == CaretPrimaryState::LongPause;
+        let key = fingerprint(text) ^ (caret as u64).rotate_left(32);
+        if matches!(self.last_processed, Some((k, was_long)) if k == key && (was_long || !long)) {
+            return skipped(diagnostics, SkipReason::Unchanged);
+        }
+        self.last_processed = Some((key, long));
 
+        let region = self.region_policy.calculate_region(text, caret);
+        let start = floor_char_boundary(text, region.start.min(caret));
+        let end = floor_char_boundary(text, region.end.min(caret));
+        diagnostics.region = Some(start..end);
+        let fragment = &text[start..end];
+        if fragment.trim().is_empty() {
+            return skipped(diagnostics, SkipReason::EmptyRegion);
+        }
 
+        // Language runs of the region, as in SweepScheduler; the corrector gets the run typed last
+        let runs = self.detector.segment(fragment);
+        let (language, language_confidence) =
+            language_detection::run_at(&runs, fragment.len()).map_or((Language::Unknown, 0.0), |r| (r.language.clone(), r.confidence));
+        diagnostics.language = Some((language.clone(), language_confidence));
+
+        let correction = self.corrector.correct(fragment, &language).filter(|c| c.text != fragment);
+        let mut edits = Vec::new();
+        if let Some(correction) = &correction {
+            diagnostics.confidence = Some(correction.confidence);
+            // Region ends at or before the caret, so its own end is the caret bound
+            let safe = CaretSafeDiff::new(caret - start).calculate(fragment, &correction.text);
+            diagnostics.clipped = safe.clipped.clone();
+            for edit in safe.edits() {
+                let range = edit.range.start + start..edit.range.end + start;
+                if self.admit(text, caret, &range, correction.confidence, EditType::Lm, now_ms) {
+                    edits.push(TextEdit::new(range, edit.replacement));
+                } else {
+                    diagnostics.gated += 1;
+                }
+            }
+        }
+
+        // REQ-LANGUAGE-GATING: tone only inside English runs, and never over a correction
+        let english: Vec<Range<usize>> =
+            runs.iter().filter(|r| r.language == Language::English).map(|r| r.range.start + start..r.range.end + start).collect();
+        let mut toned = false;
+        if long && !english.is_empty() {
+            self.tone_worker.settings = self.tone();
+            for proposal in self.tone_worker.plan(text, caret) {
+                let range = proposal.edit.range;
+                let overlaps = edits.iter().any(|e| e.range.start < range.end && range.start < e.range.end);
+                let inside = english.iter().any(|r| r.start <= range.start && range.end <= r.end);
+                if !inside || overlaps {
+                    continue;
+                }
+                toned = true;
+                if self.admit(text, caret, &range, proposal.confidence, EditType::Tone, now_ms) {
+                    edits.push(TextEdit::new(range, proposal.edit.replacement));
+                } else {
+                    diagnostics.gated += 1;
+                }
+            }
+        }
+        if correction.is_none() && !toned {
+            return skipped(diagnostics, SkipReason::NoCorrection);
+        }
+        if edits.is_empty() {
+            return skipped(diagnostics, SkipReason::Gated);
+        }
+        edits.sort_by_key(|e| e.range.start);
+        TickOutput { edits, diagnostics }
+    }
+
+    /// Confidence gate for an edit at `range`: the proposal's score against the dynamic
+    /// thresholds, raised where the user undid similar edits; records the edit when it passes
+    fn admit(&mut self, text: &str, caret: usize, range: &Range<usize>, confidence: f32, kind: EditType, now_ms: u64) -> bool {
+        let score = score_proposal(text, caret, range, confidence, 1.0);
+        let base = GateThresholds { commit: self.confidence.base_threshold, ..GateThresholds::default() };
+        // Tone runs on a long pause over whole sentences: nearness to the caret barely raises the bar
+        let sensitivity = if kind == EditType::Tone { 0.1 } else { 1.0 };
+        let input = DynamicThresholdsInput { base, sensitivity, ..DynamicThresholdsInput::new(caret, range.clone(), kind) };
+        let thresholds = self.adaptive.thresholds(&input, now_ms);
+        let admitted = apply_thresholds(&score, &thresholds, kind == EditType::Tone) == GateDecision::Commit;
+        if admitted {
+            self.adaptive.record_applied(kind, range.clone(), now_ms);
+        }
+        admitted
+    }
+}
+
+fn skipped(mut diagnostics: TickDiagnostics, reason: SkipReason) -> TickOutput {
+    diagnostics.skipped = Some(reason);
+    TickOutput { edits: Vec::new(), diagnostics }
+}
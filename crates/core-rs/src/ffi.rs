@@ -16,12 +16,18 @@
   • HOW  ▸ Minimal extern "C" signatures; memory mgmt helpers later
 */
 
+// Hosts own pointer validity; every entry point null-checks before dereferencing.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 #[repr(C)]
 pub struct MTString {
     pub ptr: *mut u8,
     pub len: usize,
 }
 
+// Text crosses the boundary as UTF-8 bytes (`text_len` in bytes); every
+// position (caret, band, edit ranges) is in UTF-16 code units like NSRange.
+
 #[repr(C)]
 pub struct MTCaretEvent {
     pub text_ptr: *const u8,
@@ -48,10 +54,39 @@ pub struct MTBandRange {
     pub valid: bool,
 }
 
+#[repr(C)]
+pub struct MTTextEdit {
+    pub start: u32,
+    pub end: u32,
+    pub replacement: MTString,
+}
+
+#[repr(C)]
+pub struct MTTextEditList {
+    pub ptr: *mut MTTextEdit,
+    pub len: usize,
+}
+
+fn into_mt_string(s: &str) -> MTString {
+    if s.is_empty() {
+        return MTString { ptr: std::ptr::null_mut(), len: 0 };
+    }
+    let mut boxed = s.as_bytes().to_vec().into_boxed_slice();
+    let ptr = boxed.as_mut_ptr();
+    let len = boxed.len();
+    std::mem::forget(boxed);
+    MTString { ptr, len }
+}
+
+unsafe fn str_from_raw<'a>(ptr: *const u8, len: usize) -> Option<&'a str> {
+    if ptr.is_null() { return None; }
+    std::str::from_utf8(std::slice::from_raw_parts(ptr, len)).ok()
+}
+
 // Core version and memory management
 #[no_mangle]
 pub extern "C" fn mind_type_core_version() -> MTString {
-    let s = format!("{}","0.4.0-alpha.0");
+    let s = "0.4.0-alpha.0".to_string();
     let bytes = s.into_bytes();
     let len = bytes.len();
     let mut boxed = bytes.into_boxed_slice();
@@ -93,16 +128,29 @@ pub extern "C" fn mind_type_caret_monitor_update(
     unsafe {
         let text_slice = std::slice::from_raw_parts(event.text_ptr, event.text_len);
         if let Ok(text) = std::str::from_utf8(text_slice) {
+            // Pauses carry no input; the monitor derives them from elapsed time.
+            if event.event_kind == 1 {
+                return (*monitor).flush(event.timestamp_ms) > 0;
+            }
             let rust_event = crate::caret_monitor::CaretEvent {
                 kind: match event.event_kind {
                     0 => crate::caret_monitor::EventKind::Input,
-                    1 => crate::caret_monitor::EventKind::Pause,
-                    2 => crate::caret_monitor::EventKind::Selection,
+                    2 => crate::caret_monitor::EventKind::SelectionChange,
                     _ => crate::caret_monitor::EventKind::Input,
                 },
-                text: text.to_string(),
-                caret: event.caret as usize,
                 timestamp_ms: event.timestamp_ms,
+                caret: event.caret,
+                text_len: crate::offsets::len_in(text, crate::offsets::OffsetUnit::Utf16) as u32,
+                selection: crate::caret_monitor::SelectionFacet {
+                    collapsed: true,
+                    start: event.caret,
+                    end: event.caret,
+                },
+                input_modality: Default::default(),
+                field_kind: Default::default(),
+                ime_active: false,
+                blocked: false,
+                input_type: None,
             };
             (*monitor).update(rust_event)
         } else {
@@ -137,15 +185,15 @@ pub extern "C" fn mind_type_caret_monitor_get_snapshots(
         for (i, snapshot) in drained.iter().take(count).enumerate() {
             let mt_snapshot = MTCaretSnapshot {
                 primary: match snapshot.primary {
-                    crate::caret_monitor::PrimaryState::Typing => 0,
-                    crate::caret_monitor::PrimaryState::ShortPause => 1,
-                    crate::caret_monitor::PrimaryState::LongPause => 2,
-                    crate::caret_monitor::PrimaryState::SelectionActive => 3,
-                    crate::caret_monitor::PrimaryState::Blur => 4,
+                    crate::caret_monitor::CaretPrimaryState::Typing => 0,
+                    crate::caret_monitor::CaretPrimaryState::ShortPause => 1,
+                    crate::caret_monitor::CaretPrimaryState::LongPause => 2,
+                    crate::caret_monitor::CaretPrimaryState::SelectionActive => 3,
+                    crate::caret_monitor::CaretPrimaryState::Blur => 4,
                     _ => 0,
                 },
-                caret: snapshot.caret as u32,
-                text_len: snapshot.text_len as u32,
+                caret: snapshot.caret,
+                text_len: snapshot.text_len,
                 timestamp_ms: snapshot.timestamp_ms,
                 blocked: snapshot.blocked,
                 ime_active: snapshot.ime_active,
@@ -169,12 +217,7 @@ pub extern "C" fn mind_type_extract_fragment(text_ptr: *const u8, text_len: usiz
         if let Ok(text) = std::str::from_utf8(text_slice) {
             let extractor = crate::fragment::FragmentExtractor::new();
             if let Some(fragment) = extractor.extract_fragment(text) {
-                let bytes = fragment.into_bytes();
-                let len = bytes.len();
-                let mut boxed = bytes.into_boxed_slice();
-                let ptr = boxed.as_mut_ptr();
-                std::mem::forget(boxed);
-                return MTString { ptr, len };
+                return into_mt_string(fragment);
             }
         }
         MTString { ptr: std::ptr::null_mut(), len: 0 }
@@ -195,40 +238,96 @@ pub extern "C" fn mind_type_compute_band(
     unsafe {
         let text_slice = std::slice::from_raw_parts(text_ptr, text_len);
         if let Ok(text) = std::str::from_utf8(text_slice) {
-            // Simple band computation - take last ~50 chars before caret
-            let caret_pos = std::cmp::min(caret as usize, text.len());
-            let start = if caret_pos > 50 { caret_pos - 50 } else { 0 };
-            
-            // Find word boundaries
-            let start_boundary = text[..start].rfind(char::is_whitespace)
-                .map(|i| i + 1)
-                .unwrap_or(start);
+            let caret_pos = crate::offsets::utf16_to_byte_clamped(text, caret as usize);
+            let region = crate::active_region::ActiveRegionPolicy::default().calculate_region(text, caret_pos);
+            let to_utf16 = |b: usize| crate::offsets::byte_to_utf16(text, b).unwrap_or(0) as u32;
             
             return MTBandRange {
-                start: start_boundary as u32,
-                end: caret_pos as u32,
-                valid: start_boundary < caret_pos,
+                start: to_utf16(region.start),
+                end: to_utf16(region.end),
+                valid: region.start < region.end,
             };
         }
         MTBandRange { start: 0, end: 0, valid: false }
     }
 }
 
-// Tone setting (placeholder for future LM integration)
+// Caret-safe diff: edits (UTF-16 ranges) that turn `original` into
+// `modified` without touching text at or after `caret`.
 #[no_mangle]
-pub extern "C" fn mind_type_set_tone(enabled: bool, target_ptr: *const u8, target_len: usize) -> bool {
-    if target_ptr.is_null() { return false; }
-    
-    unsafe {
-        let target_slice = std::slice::from_raw_parts(target_ptr, target_len);
-        if let Ok(target) = std::str::from_utf8(target_slice) {
-            // TODO: Store tone settings in global state
-            log::info!("Tone setting: enabled={}, target={}", enabled, target);
-            return true;
+pub extern "C" fn mind_type_caret_safe_diff(
+    original_ptr: *const u8,
+    original_len: usize,
+    modified_ptr: *const u8,
+    modified_len: usize,
+    caret: u32,
+) -> MTTextEditList {
+    let empty = MTTextEditList { ptr: std::ptr::null_mut(), len: 0 };
+    let (original, modified) = unsafe {
+        match (str_from_raw(original_ptr, original_len), str_from_raw(modified_ptr, modified_len)) {
+            (Some(o), Some(m)) => (o, m),
+            _ => return empty,
         }
-        false
+    };
+    let caret_byte = crate::offsets::utf16_to_byte_clamped(original, caret as usize);
+    let result = crate::diff::CaretSafeDiff::new(caret_byte).calculate(original, modified);
+    let edits: Vec<MTTextEdit> = result
+        .edits()
+        .iter()
+        .filter_map(|e| e.to_unit(original, crate::offsets::OffsetUnit::Utf16))
+        .map(|e| MTTextEdit {
+            start: e.range.start as u32,
+            end: e.range.end as u32,
+            replacement: into_mt_string(&e.replacement),
+        })
+        .collect();
+    if edits.is_empty() {
+        return empty;
     }
+    let mut boxed = edits.into_boxed_slice();
+    let list = MTTextEditList { ptr: boxed.as_mut_ptr(), len: boxed.len() };
+    std::mem::forget(boxed);
+    list
 }
 
+#[no_mangle]
+pub extern "C" fn mind_type_free_edits(list: MTTextEditList) {
+    if list.ptr.is_null() || list.len == 0 { return; }
+    unsafe {
+        let edits = Vec::from_raw_parts(list.ptr, list.len, list.len);
+        for edit in edits {
+            mind_type_core_free_string(edit.replacement);
+        }
+    }
+}
 
+// Tone setting: process-wide, used on long pauses by every engine without its own override.
+// `target` is "None", "Casual" or "Professional"; unknown targets are rejected.
+#[no_mangle]
+pub extern "C" fn mind_type_set_tone(enabled: bool, target_ptr: *const u8, target_len: usize) -> bool {
+    let target = unsafe { str_from_raw(target_ptr, target_len) };
+    match target.and_then(crate::workers::tone::ToneTarget::from_name) {
+        Some(target) => {
+            log::info!("Tone setting: enabled={}, target={}", enabled, target.name());
+            crate::workers::tone::set_global_tone(crate::workers::tone::ToneSettings { enabled, target });
+            true
+        }
+        None => false,
+    }
+}
 
+// Keyboard layout reported by the host; running engines and sweep schedulers
+// switch to it on their next tick.
+// `name` is "QWERTY", "QWERTZ", "AZERTY" or "Dvorak"; unknown names are rejected.
+#[no_mangle]
+pub extern "C" fn mind_type_set_keyboard_layout(name_ptr: *const u8, name_len: usize) -> bool {
+    let name = unsafe { str_from_raw(name_ptr, name_len) };
+    match name.and_then(crate::typo_model::KeyboardLayout::from_name) {
+        Some(layout) => {
+            log::info!("Keyboard layout: {}", layout.name());
+            crate::typo_model::set_global_layout(layout);
+            true
+        }
+        None => false,
+    }
+}
@@ -1,36 +1,251 @@
+use std::ops::Range;
+
 use log::debug;
-use unicode_segmentation::UnicodeSegmentation;
+use serde::{Deserialize, Serialize};
+
+use crate::language_detection::Language;
+use crate::offsets::floor_char_boundary;
+
+/// Sentence-final punctuation, including runs like "?!" and "..."
+const SENTENCE_TERMINATORS: &[char] = &[
+    '.', '?', '!', '…', '‼', '⁇', '⁈', '⁉', '。', '｡', '！', '？', '؟', '۔', '।', '॥',
+];
 
-const SENTENCE_TERMINATORS: &[char] = &['.', '?', '!', '。', '\n'];
+/// Full-width terminators end a sentence without any following whitespace
+const CJK_TERMINATORS: &[char] = &['。', '｡', '！', '？'];
 
-pub struct FragmentExtractor;
+/// Closing quotes and brackets that belong to the sentence they follow
+const CLOSERS: &[char] = &[
+    '"', '\'', '”', '’', '»', '›', ')', ']', '}', '）', '］', '｝', '」', '』', '】', '〉', '》', '〕',
+];
+
+const EN_ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "e.g", "i.e", "cf", "approx", "fig",
+    "inc", "ltd", "co", "jan", "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov",
+    "dec", "a.m", "p.m", "u.s", "dept", "est", "vol",
+];
+const ES_ABBREVIATIONS: &[&str] = &[
+    "sr", "sra", "srta", "dr", "dra", "ud", "uds", "p.ej", "pág", "núm", "aprox", "avda", "d", "dña",
+];
+const FR_ABBREVIATIONS: &[&str] = &[
+    "m", "mm", "mme", "mlle", "dr", "p.ex", "c.-à-d", "env", "cf", "av", "apr", "boul", "chap",
+];
+const DE_ABBREVIATIONS: &[&str] = &[
+    "z.b", "bzw", "d.h", "u.a", "dr", "hr", "fr", "nr", "ca", "vgl", "evtl", "s", "bspw", "ggf", "inkl",
+    "str", "u.u", "o.ä",
+];
+
+/// Abbreviations (lowercase, without the final dot) that do not end a sentence
+pub fn abbreviations(language: &Language) -> &'static [&'static str] {
+    match language {
+        Language::English => EN_ABBREVIATIONS,
+        Language::Spanish => ES_ABBREVIATIONS,
+        Language::French => FR_ABBREVIATIONS,
+        Language::German => DE_ABBREVIATIONS,
+        _ => &[],
+    }
+}
+
+/// Whether `token` (without its final dot, any case) is a known abbreviation
+pub fn is_abbreviation(language: &Language, token: &str) -> bool {
+    let token = token.to_lowercase();
+    let languages: &[Language] = if *language == Language::Unknown {
+        &[Language::English, Language::Spanish, Language::French, Language::German]
+    } else {
+        std::slice::from_ref(language)
+    };
+    languages.iter().any(|l| abbreviations(l).contains(&token.as_str()))
+}
+
+/// A sentence as a trimmed byte range of the text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sentence {
+    pub range: Range<usize>,
+    /// Ended by punctuation or a line break (false for the sentence still being typed)
+    pub terminated: bool,
+}
+
+/// Completed sentence(s) before the caret plus the surrounding context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fragment {
+    /// Most recent completed sentence(s) ending at or before the caret
+    pub range: Range<usize>,
+    /// Earlier sentences through the text typed up to the caret
+    pub context: Range<usize>,
+}
+
+pub struct FragmentExtractor {
+    /// Selects the abbreviation list; `Unknown` checks every list
+    pub language: Language,
+    /// Completed sentences returned in `Fragment::range`
+    pub max_sentences: usize,
+    /// Extra sentences before the fragment included in `Fragment::context`
+    pub context_sentences: usize,
+}
+
+impl Default for FragmentExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl FragmentExtractor {
     pub fn new() -> Self {
-        FragmentExtractor
+        Self::for_language(Language::Unknown)
     }
 
+    pub fn for_language(language: Language) -> Self {
+        FragmentExtractor { language, max_sentences: 1, context_sentences: 2 }
+    }
+
+    /// Last completed sentence of `text`, assuming the caret is at the end
     pub fn extract_fragment<'a>(&self, text: &'a str) -> Option<&'a str> {
         debug!("Attempting to extract fragment from text with length {}", text.len());
-        
-        let last_sentence = text.unicode_sentences().last();
-        
-        if let Some(sentence) = last_sentence {
-            if sentence.trim().is_empty() {
-                return None;
+        let fragment = self.extract(text, text.len())?;
+        debug!("Found fragment at {:?}", fragment.range);
+        Some(&text[fragment.range])
+    }
+
+    /// Most recent completed sentence(s) ending at or before byte `caret`
+    pub fn extract(&self, text: &str, caret: usize) -> Option<Fragment> {
+        let caret = floor_char_boundary(text, caret);
+        let done: Vec<Sentence> = self
+            .segment(text)
+            .into_iter()
+            .filter(|s| s.terminated && s.range.end <= caret)
+            .collect();
+        let last = done.len().checked_sub(1)?;
+        let first = last + 1 - self.max_sentences.clamp(1, done.len());
+        let context_first = first.saturating_sub(self.context_sentences);
+        let context_end = text[..caret].trim_end().len().max(done[last].range.end);
+        Some(Fragment {
+            range: done[first].range.start..done[last].range.end,
+            context: done[context_first].range.start..context_end,
+        })
+    }
+
+    /// Split `text` into sentences; a trailing unterminated sentence is included
+    pub fn segment(&self, text: &str) -> Vec<Sentence> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let byte_at = |i: usize| chars.get(i).map_or(text.len(), |&(b, _)| b);
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < chars.len() {
+            let (pos, c) = chars[i];
+            if c == '\n' {
+                push_sentence(text, start..pos, true, &mut sentences);
+                start = pos + 1;
+                i += 1;
+                continue;
+            }
+            if !SENTENCE_TERMINATORS.contains(&c) {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            let mut j = i;
+            while j < chars.len() && SENTENCE_TERMINATORS.contains(&chars[j].1) {
+                j += 1;
             }
-            // A simple heuristic: if the last sentence ends with a terminator,
-            // we might be at a good point to extract.
-            if let Some(last_char) = sentence.trim().chars().last() {
-                if SENTENCE_TERMINATORS.contains(&last_char) {
-                    debug!("Found fragment: '{}'", sentence.trim());
-                    return Some(sentence.trim());
-                }
+            let run_end = j;
+            j = skip_closers(&chars, j);
+            if self.is_boundary(text, &chars, run_start..run_end, j) {
+                let end = byte_at(j);
+                push_sentence(text, start..end, true, &mut sentences);
+                start = end;
+            }
+            i = j.max(i + 1);
+        }
+        push_sentence(text, start..text.len(), false, &mut sentences);
+        sentences
+    }
+
+    /// Whether the terminator run `run`, with closers up to `after`, ends a sentence
+    fn is_boundary(&self, text: &str, chars: &[(usize, char)], run: Range<usize>, after: usize) -> bool {
+        if chars[run.clone()].iter().any(|(_, c)| CJK_TERMINATORS.contains(c)) {
+            return true;
+        }
+        // "3.14", "example.com", "?q=1": no whitespace after the run
+        if chars.get(after).is_some_and(|(_, c)| !c.is_whitespace()) {
+            return false;
+        }
+        let only_dots = chars[run.clone()].iter().all(|(_, c)| matches!(c, '.' | '…'));
+        if !only_dots {
+            return true;
+        }
+        let rest = &text[chars.get(after).map_or(text.len(), |&(b, _)| b)..];
+        if run.len() == 1 && chars[run.start].1 == '.' && self.ends_with_abbreviation(&text[..chars[run.start].0], rest) {
+            return false;
+        }
+        // "e.g. the", "well... maybe": a lowercase continuation is the same sentence
+        let next = chars[after..].iter().map(|&(_, c)| c).find(|c| !c.is_whitespace());
+        !next.is_some_and(char::is_lowercase)
+    }
+
+    fn ends_with_abbreviation(&self, before_dot: &str, after_dot: &str) -> bool {
+        let token_start = before_dot
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !(c.is_alphanumeric() || c == '.' || c == '-'))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let token = before_dot[token_start..].to_lowercase();
+        if token.is_empty() {
+            return false;
+        }
+        let mut letters = before_dot[token_start..].chars();
+        if let (Some(letter), None) = (letters.next(), letters.next()) {
+            if letter.is_alphabetic() && !is_abbreviation(&self.language, &token) {
+                return self.is_initial(letter, &before_dot[..token_start], after_dot);
             }
         }
-        
-        debug!("No fragment found.");
-        None
+        // German ordinals: "am 3. Mai"
+        if self.language == Language::German && token.chars().all(|c| c.is_ascii_digit()) {
+            return true;
+        }
+        is_abbreviation(&self.language, &token)
+    }
+
+    /// Initials such as "J. R. R. Tolkien": an uppercase letter (never the
+    /// pronoun "I") followed by another initial, or by a capitalised name when
+    /// the text before it is a sentence start, a capitalised word or an initial.
+    /// "Use plan B. Next step" splits.
+    fn is_initial(&self, letter: char, before: &str, after_dot: &str) -> bool {
+        if !letter.is_uppercase() || (letter == 'I' && self.language == Language::English) {
+            return false;
+        }
+        let next = after_dot.split_whitespace().next().unwrap_or_default();
+        if !next.chars().next().is_some_and(char::is_uppercase) {
+            return false;
+        }
+        let mut next_chars = next.chars();
+        if let (Some(_), Some('.')) = (next_chars.next(), next_chars.next()) {
+            return true;
+        }
+        let previous = before.split_whitespace().next_back().unwrap_or_default();
+        previous.is_empty()
+            || previous.ends_with(SENTENCE_TERMINATORS)
+            || previous.chars().next().is_some_and(char::is_uppercase)
+    }
+}
+
+/// Advance past closing quotes/brackets, including French "… »" with a space before »
+fn skip_closers(chars: &[(usize, char)], mut j: usize) -> usize {
+    loop {
+        match chars.get(j).map(|&(_, c)| c) {
+            Some(c) if CLOSERS.contains(&c) => j += 1,
+            Some(' ' | '\u{a0}' | '\u{202f}') if chars.get(j + 1).is_some_and(|&(_, c)| c == '»') => j += 2,
+            _ => return j,
+        }
+    }
+}
+
+fn push_sentence(text: &str, range: Range<usize>, terminated: bool, out: &mut Vec<Sentence>) {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.start + slice.trim_end().len();
+    if start < end {
+        out.push(Sentence { range: start..end, terminated });
     }
 }
 
@@ -72,4 +287,95 @@ mod tests {
         let text = "";
         assert_eq!(extractor.extract_fragment(text), None);
     }
-} 
\ No newline at end of file
+
+    fn sentences<'a>(extractor: &FragmentExtractor, text: &'a str) -> Vec<&'a str> {
+        extractor.segment(text).into_iter().map(|s| &text[s.range]).collect()
+    }
+
+    #[test]
+    fn test_caret_limits_fragment() {
+        let extractor = FragmentExtractor::new();
+        let text = "One is done. Two is done. Three is in prog";
+        let caret = text.find("Two").unwrap() + 3;
+        let fragment = extractor.extract(text, caret).unwrap();
+        assert_eq!(&text[fragment.range], "One is done.");
+        assert_eq!(&text[fragment.context], "One is done. Two");
+
+        let fragment = extractor.extract(text, text.len()).unwrap();
+        assert_eq!(&text[fragment.range.clone()], "Two is done.");
+        assert_eq!(&text[fragment.context], text);
+        assert_eq!(extractor.extract(text, 5), None);
+    }
+
+    #[test]
+    fn test_multiple_sentences() {
+        let mut extractor = FragmentExtractor::new();
+        extractor.max_sentences = 2;
+        extractor.context_sentences = 0;
+        let text = "A one. B two. C three. D";
+        let fragment = extractor.extract(text, text.len()).unwrap();
+        assert_eq!(&text[fragment.range], "B two. C three.");
+        assert_eq!(&text[fragment.context], "B two. C three. D");
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let en = FragmentExtractor::for_language(Language::English);
+        assert_eq!(
+            sentences(&en, "I met Dr. Smith, e.g. at work. J. R. R. Tolkien wrote it."),
+            ["I met Dr. Smith, e.g. at work.", "J. R. R. Tolkien wrote it."]
+        );
+        // Still typing after an abbreviation: nothing is complete yet
+        assert_eq!(en.extract_fragment("Ask Dr."), None);
+        // A single letter is not always an initial
+        assert_eq!(sentences(&en, "It was I. Then we left"), ["It was I.", "Then we left"]);
+        assert_eq!(sentences(&en, "Use plan B. Next step"), ["Use plan B.", "Next step"]);
+        assert_eq!(sentences(&en, "We saw John F. Kennedy. Then we left"), ["We saw John F. Kennedy.", "Then we left"]);
+
+        let de = FragmentExtractor::for_language(Language::German);
+        assert_eq!(sentences(&de, "Wir kommen am 3. Mai, z.B. mit Bus. Gut."), ["Wir kommen am 3. Mai, z.B. mit Bus.", "Gut."]);
+        let es = FragmentExtractor::for_language(Language::Spanish);
+        assert_eq!(sentences(&es, "Hablé con la Sra. Pérez. ¿Vienes?"), ["Hablé con la Sra. Pérez.", "¿Vienes?"]);
+    }
+
+    #[test]
+    fn test_decimals_urls_and_ellipses() {
+        let extractor = FragmentExtractor::new();
+        assert_eq!(
+            sentences(&extractor, "Pi is 3.14 roughly. See https://example.com/a.html?x=1! Done"),
+            ["Pi is 3.14 roughly.", "See https://example.com/a.html?x=1!", "Done"]
+        );
+        assert_eq!(
+            sentences(&extractor, "Well... maybe not. Wait… Then go?!"),
+            ["Well... maybe not.", "Wait…", "Then go?!"]
+        );
+    }
+
+    #[test]
+    fn test_quotes_and_brackets() {
+        let extractor = FragmentExtractor::new();
+        assert_eq!(
+            sentences(&extractor, "He said \"stop.\" (See above.) «Oui. » ‘Fine!’ Next"),
+            ["He said \"stop.\"", "(See above.)", "«Oui. »", "‘Fine!’", "Next"]
+        );
+    }
+
+    #[test]
+    fn test_cjk_and_rtl() {
+        let extractor = FragmentExtractor::new();
+        assert_eq!(sentences(&extractor, "你好！「走吧。」好的"), ["你好！", "「走吧。」", "好的"]);
+        let arabic = "كيف حالك؟ أنا بخير. شكرا";
+        assert_eq!(sentences(&extractor, arabic), ["كيف حالك؟", "أنا بخير.", "شكرا"]);
+        assert_eq!(extractor.extract_fragment(arabic), Some("أنا بخير."));
+        let hebrew = "שלום. מה שלומך?";
+        assert_eq!(extractor.extract_fragment(hebrew), Some("מה שלומך?"));
+    }
+
+    #[test]
+    fn test_line_breaks_end_sentences() {
+        let extractor = FragmentExtractor::new();
+        let segments = extractor.segment("Title\nBody text");
+        assert!(segments[0].terminated);
+        assert!(!segments[1].terminated);
+    }
+}
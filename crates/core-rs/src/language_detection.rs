@@ -1,22 +1,28 @@
 /*╔══════════════════════════════════════════════════════════════╗
   ║  ░  L A N G U A G E   D E T E C T I O N  ░░░░░░░░░░░░░░░░░░  ║
   ║                                                              ║
-  ║                                                              ║
-  ║                                                              ║
-  ║                                                              ║
-  ║           ╌╌  P L A C E H O L D E R  ╌╌                      ║
-  ║                                                              ║
-  ║                                                              ║
-  ║                                                              ║
+  ║   Script first, then character trigrams for Latin text.      ║
   ║                                                              ║
   ╚══════════════════════════════════════════════════════════════╝
   • WHAT ▸ Language detection for text processing
-  • WHY  ▸ Apply language-specific rules and transformations
-  • HOW  ▸ Statistical analysis and pattern matching
+  • WHY  ▸ Apply language-specific rules and transformations;
+           REQ-LANGUAGE-GATING needs to know when text is English
+  • HOW  ▸ Han/kana/Hangul/Cyrillic decide by script alone; Latin
+           text is scored against trigram profiles built from the
+           embedded samples in data/lang (naive Bayes, tempered);
+           mixed documents are split into clause-level runs, and a
+           run only switches language on a confident, long clause
 */
 
+use std::collections::HashMap;
+use std::ops::Range;
+
+use lazy_static::lazy_static;
+
+use crate::offsets::floor_char_boundary;
+
 /// Supported languages for detection
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Language {
     /// English language
     English,
@@ -26,6 +32,20 @@ pub enum Language {
     French,
     /// German language
     German,
+    /// Italian language
+    Italian,
+    /// Portuguese language
+    Portuguese,
+    /// Dutch language
+    Dutch,
+    /// Chinese (Han script without kana)
+    Chinese,
+    /// Japanese (kana, possibly mixed with Han)
+    Japanese,
+    /// Korean (Hangul)
+    Korean,
+    /// Russian (stands in for Cyrillic script)
+    Russian,
     /// Unknown or unsupported language
     Unknown,
 }
@@ -38,9 +58,168 @@ impl Language {
             Language::Spanish => "es",
             Language::French => "fr",
             Language::German => "de",
+            Language::Italian => "it",
+            Language::Portuguese => "pt",
+            Language::Dutch => "nl",
+            Language::Chinese => "zh",
+            Language::Japanese => "ja",
+            Language::Korean => "ko",
+            Language::Russian => "ru",
             Language::Unknown => "unknown",
         }
     }
+
+    /// Inverse of `code` (case-insensitive; region suffixes like "en-GB" are ignored)
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+        ALL.iter().chain([&Language::Unknown]).find(|l| l.code() == primary).cloned()
+    }
+}
+
+/// Languages recognised by script alone; a switch to or from them needs no length
+fn decided_by_script(language: &Language) -> bool {
+    matches!(language, Language::Chinese | Language::Japanese | Language::Korean | Language::Russian)
+}
+
+/// Every detectable language
+const ALL: &[Language] = &[
+    Language::English,
+    Language::Spanish,
+    Language::French,
+    Language::German,
+    Language::Italian,
+    Language::Portuguese,
+    Language::Dutch,
+    Language::Chinese,
+    Language::Japanese,
+    Language::Korean,
+    Language::Russian,
+];
+
+/// Training samples for the Latin-script languages
+const SAMPLES: &[(Language, &str)] = &[
+    (Language::English, include_str!("../data/lang/en.txt")),
+    (Language::Spanish, include_str!("../data/lang/es.txt")),
+    (Language::French, include_str!("../data/lang/fr.txt")),
+    (Language::German, include_str!("../data/lang/de.txt")),
+    (Language::Italian, include_str!("../data/lang/it.txt")),
+    (Language::Portuguese, include_str!("../data/lang/pt.txt")),
+    (Language::Dutch, include_str!("../data/lang/nl.txt")),
+];
+
+/// Only the first this-many bytes are examined
+const MAX_SAMPLE_BYTES: usize = 1_000;
+/// Add-α smoothing and assumed trigram vocabulary
+const SMOOTHING: f32 = 0.5;
+const VOCABULARY: f32 = 8_000.0;
+/// Log-likelihoods are scaled by this before normalising: naive Bayes treats
+/// overlapping trigrams as independent and is overconfident without it
+const TEMPERATURE: f32 = 0.8;
+/// A Latin clause needs this many letters to start a new run (hysteresis)
+const MIN_SWITCH_LETTERS: usize = 12;
+
+struct Profile {
+    language: Language,
+    counts: HashMap<[char; 3], u32>,
+    total: u32,
+}
+
+impl Profile {
+    fn log_probability(&self, trigram: &[char; 3]) -> f32 {
+        let count = self.counts.get(trigram).copied().unwrap_or(0) as f32;
+        ((count + SMOOTHING) / (self.total as f32 + SMOOTHING * VOCABULARY)).ln()
+    }
+}
+
+lazy_static! {
+    static ref PROFILES: Vec<Profile> = SAMPLES
+        .iter()
+        .map(|(language, sample)| {
+            let mut counts = HashMap::new();
+            let mut total = 0;
+            for trigram in trigrams(sample) {
+                *counts.entry(trigram).or_insert(0) += 1;
+                total += 1;
+            }
+            Profile { language: language.clone(), counts, total }
+        })
+        .collect();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Script {
+    Latin,
+    Cyrillic,
+    Han,
+    Kana,
+    Hangul,
+    Other,
+}
+
+fn script(c: char) -> Script {
+    match c as u32 {
+        0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F | 0x1E00..=0x1EFF => Script::Latin,
+        0x400..=0x52F => Script::Cyrillic,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => Script::Han,
+        0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9D => Script::Kana,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        _ => Script::Other,
+    }
+}
+
+/// Lowercased letter trigrams of each word, padded with a space on both sides
+fn trigrams(text: &str) -> Vec<[char; 3]> {
+    let mut out = Vec::new();
+    for word in text.split(|c: char| !c.is_alphabetic()).filter(|w| !w.is_empty()) {
+        let chars: Vec<char> = std::iter::once(' ').chain(word.chars().flat_map(char::to_lowercase)).chain([' ']).collect();
+        out.extend(chars.windows(3).map(|w| [w[0], w[1], w[2]]));
+    }
+    out
+}
+
+/// Clause ranges covering `text`: a break after sentence or clause punctuation
+/// followed by whitespace, or after a newline or CJK punctuation
+fn clauses(text: &str) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    // Inside the punctuation/whitespace after a clause; `split` once a space was seen
+    let (mut gap, mut split) = (false, false);
+    for (i, c) in text.char_indices() {
+        if matches!(c, '.' | '!' | '?' | ';' | ':' | ',') {
+            gap = true;
+        } else if matches!(c, '\n' | '。' | '！' | '？' | '；' | '，' | '、') {
+            gap = true;
+            split = true;
+        } else if gap && c.is_whitespace() {
+            split = true;
+        } else {
+            if split && i > start {
+                out.push(start..i);
+                start = i;
+            }
+            gap = false;
+            split = false;
+        }
+    }
+    if start < text.len() {
+        out.push(start..text.len());
+    }
+    out
+}
+
+/// A stretch of text in one language
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageRun {
+    /// Byte range; runs are contiguous and cover the whole text
+    pub range: Range<usize>,
+    pub language: Language,
+    /// Letter-weighted probability of `language` over the run's clauses
+    pub confidence: f32,
+}
+
+/// Run covering the text just before byte `pos` (the first run at 0)
+pub fn run_at(runs: &[LanguageRun], pos: usize) -> Option<&LanguageRun> {
+    runs.iter().find(|r| r.range.start < pos && pos <= r.range.end).or_else(|| runs.first().filter(|_| pos == 0))
 }
 
 /// Language detection engine
@@ -57,14 +236,130 @@ impl LanguageDetector {
         }
     }
 
-    /// Detect language from text sample
+    /// Detect language from text sample; below `confidence_threshold` the
+    /// language is `Unknown` (the confidence still reports the best guess)
     pub fn detect(&self, text: &str) -> (Language, f32) {
-        // Stub implementation - defaults to English with high confidence
-        if text.is_empty() {
-            (Language::Unknown, 0.0)
+        match self.scores(text).into_iter().next() {
+            Some((language, confidence)) if confidence >= self.confidence_threshold => (language, confidence),
+            Some((_, confidence)) => (Language::Unknown, confidence),
+            None => (Language::Unknown, 0.0),
+        }
+    }
+
+    /// Every candidate with its probability, most likely first; empty when
+    /// the text has no letters
+    pub fn scores(&self, text: &str) -> Vec<(Language, f32)> {
+        let sample = &text[..floor_char_boundary(text, MAX_SAMPLE_BYTES)];
+
+        let mut counts: HashMap<Script, usize> = HashMap::new();
+        for c in sample.chars().filter(|c| c.is_alphabetic()) {
+            *counts.entry(script(c)).or_insert(0) += 1;
+        }
+        let letters: usize = counts.values().sum();
+        if letters == 0 {
+            return Vec::new();
+        }
+        let count = |s| counts.get(&s).copied().unwrap_or(0);
+        let cjk = count(Script::Han) + count(Script::Kana);
+
+        // Script decides on its own for non-Latin text, however short
+        let by_script = if count(Script::Hangul) * 2 > letters {
+            Some((Language::Korean, count(Script::Hangul)))
+        } else if cjk * 2 > letters {
+            let language = if count(Script::Kana) > 0 { Language::Japanese } else { Language::Chinese };
+            Some((language, cjk))
+        } else if count(Script::Cyrillic) * 2 > letters {
+            Some((Language::Russian, count(Script::Cyrillic)))
         } else {
-            (Language::English, 0.95)
+            None
+        };
+        if let Some((language, hits)) = by_script {
+            let share = hits as f32 / letters as f32;
+            return vec![(language, (0.6 + 0.4 * share).min(0.99))];
+        }
+        if count(Script::Latin) * 2 <= letters {
+            return Vec::new();
+        }
+        self.latin_scores(sample, count(Script::Latin) as f32 / letters as f32)
+    }
+
+    /// Tempered naive Bayes over the trigram profiles, scaled by the Latin share
+    fn latin_scores(&self, sample: &str, latin_share: f32) -> Vec<(Language, f32)> {
+        let grams = trigrams(sample);
+        let mut scored: Vec<(Language, f32)> = PROFILES
+            .iter()
+            .map(|p| (p.language.clone(), grams.iter().map(|g| p.log_probability(g)).sum::<f32>() * TEMPERATURE))
+            .collect();
+        let best = scored.iter().map(|(_, s)| *s).fold(f32::NEG_INFINITY, f32::max);
+        let norm: f32 = scored.iter().map(|(_, s)| (s - best).exp()).sum();
+        for (_, s) in scored.iter_mut() {
+            *s = (*s - best).exp() / norm * latin_share;
         }
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored
+    }
+
+    /// Split `text` into language runs. Each clause is detected on its own; a
+    /// confident clause in another language starts a new run only when it has
+    /// at least `MIN_SWITCH_LETTERS` letters (or its script decides), so a
+    /// loanword does not flip a run. Clauses before the first confident one
+    /// join it; with no confident clause the whole text is one `Unknown` run.
+    pub fn segment(&self, text: &str) -> Vec<LanguageRun> {
+        // (range, scores, letters) per clause
+        let clauses: Vec<_> = clauses(text)
+            .into_iter()
+            .map(|range| {
+                let clause = &text[range.clone()];
+                let letters = clause.chars().filter(|c| c.is_alphabetic()).count();
+                (range, self.scores(clause), letters)
+            })
+            .collect();
+
+        let mut current: Option<Language> = None;
+        let mut assigned: Vec<Option<Language>> = Vec::with_capacity(clauses.len());
+        for (_, scores, letters) in &clauses {
+            if let Some((top, confidence)) = scores.first().filter(|(_, c)| *c >= self.confidence_threshold) {
+                let switch = match &current {
+                    None => true,
+                    Some(language) if language == top => false,
+                    Some(language) => {
+                        *letters >= MIN_SWITCH_LETTERS || decided_by_script(top) || decided_by_script(language)
+                    }
+                };
+                if switch {
+                    log::trace!("language run → {} ({confidence:.2})", top.code());
+                    current = Some(top.clone());
+                }
+            }
+            assigned.push(current.clone());
+        }
+        let first = assigned.iter().flatten().next().cloned().unwrap_or(Language::Unknown);
+
+        let mut runs: Vec<LanguageRun> = Vec::new();
+        // Letter-weighted probability sum and letter count of the last run
+        let (mut weight, mut letters_in_run) = (0.0, 0);
+        for ((range, scores, letters), language) in clauses.into_iter().zip(assigned) {
+            let language = language.unwrap_or_else(|| first.clone());
+            let p = match language {
+                Language::Unknown => scores.first().map_or(0.0, |(_, c)| *c),
+                _ => scores.iter().find(|(l, _)| *l == language).map_or(0.0, |(_, c)| *c),
+            };
+            match runs.last_mut() {
+                Some(run) if run.language == language => {
+                    run.range.end = range.end;
+                    weight += p * letters as f32;
+                    letters_in_run += letters;
+                }
+                _ => {
+                    runs.push(LanguageRun { range, language, confidence: 0.0 });
+                    weight = p * letters as f32;
+                    letters_in_run = letters;
+                }
+            }
+            let run = runs.last_mut().expect("pushed above");
+            run.confidence = if letters_in_run == 0 { 0.0 } else { weight / letters_in_run as f32 };
+        }
+        runs
     }
 
     /// Set confidence threshold
@@ -77,4 +372,4 @@ impl Default for LanguageDetector {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,420 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  L E X I C O N  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Frequency word lists with SymSpell candidate lookup.       ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Known words, their frequencies, and every word within
+           edit distance 2 of a typed one; one lexicon per language
+  • WHY  ▸ Spelling correction without an LM inside the ≤2 ms per
+           keystroke budget on the noise path
+  • HOW  ▸ Symmetric delete index: each word is filed under the
+           deletes of its prefix, so a lookup only hashes the typed
+           word's deletes and verifies the few words they hit.
+           Words live in one string arena; the index is a sorted
+           array of (32-bit hash, word) pairs. Built-ins ship
+           front-coded as .lex files made from the .txt word lists.
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+
+use crate::language_detection::Language;
+
+/// Largest edit distance the index answers
+pub const MAX_DISTANCE: usize = 2;
+/// Only this many leading characters are indexed (SymSpell's prefix length)
+const PREFIX_LENGTH: usize = 7;
+/// Frequency given to rank 1 when a list has no counts (Zipf: f ∝ 1/rank)
+const ZIPF_TOP: u64 = 1_000_000;
+/// Leading bytes of the compact encoding, then a format version
+const MAGIC: &[u8; 4] = b"MTLX";
+const VERSION: u8 = 1;
+/// Nibble value meaning "length follows as a varint"
+const LONG: usize = 0x0f;
+
+lazy_static! {
+    static ref ENGLISH: Arc<Lexicon> = Arc::new(
+        Lexicon::from_bytes(Language::English, include_bytes!("../data/lexicon/en.lex")).expect("built-in lexicon is valid"),
+    );
+}
+
+/// Built-in English lexicon (data/lexicon/en.lex), decoded on first use
+pub fn english() -> Arc<Lexicon> {
+    ENGLISH.clone()
+}
+
+/// A lexicon word near the looked-up term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion<'a> {
+    pub word: &'a str,
+    /// Optimal string alignment distance to the term
+    pub distance: usize,
+    pub frequency: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    start: u32,
+    len: u32,
+    frequency: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    pub language: Language,
+    arena: String,
+    entries: Vec<Entry>,
+    /// (hash of a prefix delete, entry id), sorted
+    index: Vec<(u32, u32)>,
+    total: u64,
+}
+
+impl Lexicon {
+    pub fn new(language: Language) -> Self {
+        Self { language, arena: String::new(), entries: Vec::new(), index: Vec::new(), total: 0 }
+    }
+
+    /// Parse a word list: one `word` or `word count` per line, most frequent
+    /// first; `#` starts a comment. Lines without a count get a Zipf frequency
+    /// from their rank. Repeated words keep their first frequency.
+    pub fn from_word_list(language: Language, list: &str) -> Self {
+        let mut lexicon = Self::new(language);
+        let mut seen = HashSet::new();
+        let lines = list.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#'));
+        for (rank, line) in lines.enumerate() {
+            let mut parts = line.split_whitespace();
+            let Some(word) = parts.next().map(str::to_lowercase) else { continue };
+            let frequency = parts.next().and_then(|c| c.parse().ok()).unwrap_or(ZIPF_TOP / (rank as u64 + 1));
+            if seen.insert(word.clone()) {
+                lexicon.push(&word, frequency);
+            }
+        }
+        lexicon.index_all();
+        lexicon
+    }
+
+    /// Decode the compact form written by `to_bytes`
+    pub fn from_bytes(language: Language, bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC || reader.take(1)? != [VERSION] {
+            return Err(invalid("not a lexicon (bad magic or version)"));
+        }
+        let count = reader.varint()? as usize;
+        let mut words: Vec<(String, u64)> = Vec::with_capacity(count.min(bytes.len()));
+        let mut previous: Vec<u8> = Vec::new();
+        for _ in 0..count {
+            let lengths = reader.take(1)?[0];
+            let shared = reader.length(lengths >> 4)?;
+            let suffix = reader.length(lengths & 0x0f)?;
+            if shared > previous.len() {
+                return Err(invalid("shared prefix longer than the previous word"));
+            }
+            previous.truncate(shared);
+            previous.extend_from_slice(reader.take(suffix)?);
+            let word = String::from_utf8(previous.clone()).map_err(invalid)?;
+            words.push((word, reader.varint()?));
+        }
+        // Stored alphabetically; keep words() most frequent first
+        words.sort_by_key(|&(_, frequency)| std::cmp::Reverse(frequency));
+        let mut lexicon = Self::new(language);
+        for (word, frequency) in &words {
+            lexicon.push(word, *frequency);
+        }
+        lexicon.index_all();
+        Ok(lexicon)
+    }
+
+    /// Compact form: words sorted and front-coded (one byte packs the lengths
+    /// shared with the previous word and of the rest), frequencies as LEB128 varints
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut sorted: Vec<&Entry> = self.entries.iter().collect();
+        sorted.sort_by(|a, b| self.word(a).cmp(self.word(b)));
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        write_varint(&mut out, sorted.len() as u64);
+        let mut previous = "";
+        for entry in sorted {
+            let word = self.word(entry);
+            let shared: usize =
+                previous.chars().zip(word.chars()).take_while(|(a, b)| a == b).map(|(c, _)| c.len_utf8()).sum();
+            let suffix = word.len() - shared;
+            out.push((shared.min(LONG) as u8) << 4 | suffix.min(LONG) as u8);
+            for length in [shared, suffix].into_iter().filter(|&l| l >= LONG) {
+                write_varint(&mut out, length as u64);
+            }
+            out.extend_from_slice(&word.as_bytes()[shared..]);
+            write_varint(&mut out, entry.frequency);
+            previous = word;
+        }
+        out
+    }
+
+    /// Load a compact (`to_bytes`) or word list (`from_word_list`) file
+    pub fn load_file(language: Language, path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(MAGIC) {
+            return Self::from_bytes(language, &bytes);
+        }
+        Ok(Self::from_word_list(language, &String::from_utf8(bytes).map_err(invalid)?))
+    }
+
+    /// Add a word (lowercased); false if it was already present
+    pub fn insert(&mut self, word: &str, frequency: u64) -> bool {
+        let word = word.to_lowercase();
+        if word.is_empty() || self.contains(&word) {
+            return false;
+        }
+        let id = self.push(&word, frequency);
+        self.index.extend(deletes(&prefix(&word)).iter().map(|d| (hash(d), id)));
+        self.index.sort_unstable();
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.frequency(word).is_some()
+    }
+
+    /// Frequency of `word` (case-insensitive)
+    pub fn frequency(&self, word: &str) -> Option<u64> {
+        let word = word.to_lowercase();
+        self.filed_under(hash(&prefix(&word)))
+            .map(|id| &self.entries[id as usize])
+            .find(|e| self.word(e) == word)
+            .map(|e| e.frequency)
+    }
+
+    /// Share of all occurrences, for use as a prior; 0.0 for unknown words
+    pub fn prior(&self, word: &str) -> f32 {
+        match self.frequency(word) {
+            Some(f) if self.total > 0 => f as f32 / self.total as f32,
+            _ => 0.0,
+        }
+    }
+
+    /// Words in insertion order (most frequent first for decoded lexicons)
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| self.word(e))
+    }
+
+    /// Words within `max_distance` (capped at `MAX_DISTANCE`) of `term`, closest
+    /// first, then most frequent
+    pub fn lookup(&self, term: &str, max_distance: usize) -> Vec<Suggestion<'_>> {
+        let term = term.to_lowercase();
+        let max_distance = max_distance.min(MAX_DISTANCE);
+        let term_len = term.chars().count();
+        let mut seen = HashSet::new();
+        let mut out = Vec::new();
+        for delete in deletes_within(&prefix(&term), max_distance) {
+            for id in self.filed_under(hash(&delete)) {
+                if !seen.insert(id) {
+                    continue;
+                }
+                let entry = &self.entries[id as usize];
+                let word = self.word(entry);
+                if word.chars().count().abs_diff(term_len) > max_distance {
+                    continue;
+                }
+                let distance = edit_distance(&term, word);
+                if distance <= max_distance {
+                    out.push(Suggestion { word, distance, frequency: entry.frequency });
+                }
+            }
+        }
+        out.sort_by(|a, b| a.distance.cmp(&b.distance).then(b.frequency.cmp(&a.frequency)).then(a.word.cmp(b.word)));
+        out
+    }
+
+    fn word(&self, entry: &Entry) -> &str {
+        &self.arena[entry.start as usize..(entry.start + entry.len) as usize]
+    }
+
+    /// Store a new lowercase word without indexing it; returns its id
+    fn push(&mut self, word: &str, frequency: u64) -> u32 {
+        let id = self.entries.len() as u32;
+        self.entries.push(Entry { start: self.arena.len() as u32, len: word.len() as u32, frequency });
+        self.arena.push_str(word);
+        self.total += frequency;
+        id
+    }
+
+    /// Rebuild the delete index over every entry
+    fn index_all(&mut self) {
+        self.index = (0..self.entries.len() as u32)
+            .flat_map(|id| {
+                let word = self.word(&self.entries[id as usize]);
+                deletes(&prefix(word)).into_iter().map(move |d| (hash(&d), id))
+            })
+            .collect();
+        self.index.sort_unstable();
+    }
+
+    /// Entry ids filed under one delete hash (collisions are verified by callers)
+    fn filed_under(&self, key: u32) -> impl Iterator<Item = u32> + '_ {
+        let start = self.index.partition_point(|&(k, _)| k < key);
+        self.index[start..].iter().take_while(move |&&(k, _)| k == key).map(|&(_, id)| id)
+    }
+}
+
+/// Lexicons by language, for rules that pick one per input language
+#[derive(Debug, Clone, Default)]
+pub struct Lexicons {
+    by_code: HashMap<&'static str, Arc<Lexicon>>,
+}
+
+impl Lexicons {
+    /// No lexicons at all
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// The lexicons shipped in data/lexicon
+    pub fn builtin() -> Self {
+        let mut lexicons = Self::empty();
+        lexicons.insert(english());
+        lexicons
+    }
+
+    /// Register a lexicon under its language, replacing any previous one;
+    /// an `Unknown` lexicon serves every language without its own
+    pub fn insert(&mut self, lexicon: Arc<Lexicon>) -> Option<Arc<Lexicon>> {
+        self.by_code.insert(lexicon.language.code(), lexicon)
+    }
+
+    pub fn remove(&mut self, language: &Language) -> Option<Arc<Lexicon>> {
+        self.by_code.remove(language.code())
+    }
+
+    /// The lexicon for `language`, else the `Unknown` one
+    pub fn get(&self, language: &Language) -> Option<&Arc<Lexicon>> {
+        self.by_code.get(language.code()).or_else(|| self.by_code.get(Language::Unknown.code()))
+    }
+
+    /// Language codes with a lexicon, sorted
+    pub fn codes(&self) -> Vec<&'static str> {
+        let mut codes: Vec<&'static str> = self.by_code.keys().copied().collect();
+        codes.sort_unstable();
+        codes
+    }
+
+    /// Load and register `language`'s lexicon from a .lex or word list file
+    pub fn load_file(&mut self, language: Language, path: &Path) -> io::Result<()> {
+        self.insert(Arc::new(Lexicon::load_file(language, path)?));
+        Ok(())
+    }
+}
+
+/// Optimal string alignment distance (adjacent transpositions count once)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+fn prefix(word: &str) -> String {
+    word.chars().take(PREFIX_LENGTH).collect()
+}
+
+/// `word` and every string left after deleting up to `MAX_DISTANCE` characters
+fn deletes(word: &str) -> HashSet<String> {
+    deletes_within(word, MAX_DISTANCE)
+}
+
+fn deletes_within(word: &str, max_distance: usize) -> HashSet<String> {
+    let mut out = HashSet::from([word.to_string()]);
+    let mut frontier = vec![word.to_string()];
+    for _ in 0..max_distance {
+        let mut next = Vec::new();
+        for w in &frontier {
+            let chars: Vec<char> = w.chars().collect();
+            for skip in 0..chars.len() {
+                let delete: String = chars.iter().enumerate().filter(|&(k, _)| k != skip).map(|(_, c)| c).collect();
+                if out.insert(delete.clone()) {
+                    next.push(delete);
+                }
+            }
+        }
+        frontier = next;
+    }
+    out
+}
+
+/// 32-bit FNV-1a; stable across runs and cheap for short keys
+fn hash(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn invalid(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len()).ok_or_else(|| invalid("truncated lexicon"))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint too long"))
+    }
+
+    /// A packed length nibble, or the varint it defers to
+    fn length(&mut self, nibble: u8) -> io::Result<usize> {
+        match nibble as usize {
+            LONG => Ok(self.varint()? as usize),
+            short => Ok(short),
+        }
+    }
+}
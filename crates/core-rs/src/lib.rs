@@ -4,6 +4,7 @@ use crate::fragment::FragmentExtractor;
 use crate::llm::{StubStream, TokenStream};
 use crate::merge::Merger;
 
+pub mod clock;
 pub mod pause_timer;
 pub mod fragment;
 pub mod llm;
@@ -11,16 +12,23 @@ pub mod merge;
 pub mod logger;
 pub mod engine;
 pub mod confidence;
+pub mod default_thresholds;
 pub mod tapestry;
 pub mod ffi;
 pub mod caret_monitor;
 pub mod diffusion;
 pub mod diff;
+pub mod offsets;
 pub mod active_region;
 pub mod language_detection;
 pub mod scheduler;
 pub mod lm;
 pub mod workers;
+pub mod replay;
+pub mod staging_buffer;
+pub mod conflict;
+pub mod typo_model;
+pub mod lexicon;
 
 #[wasm_bindgen]
 pub fn init_logger() {
@@ -37,6 +45,44 @@ pub fn get_logs() -> JsValue {
     logger::get_logs()
 }
 
+/// Caret-safe edits turning `original` into `modified`; caret and ranges are UTF-16 code units
+#[wasm_bindgen]
+pub fn caret_safe_diff(original: &str, modified: &str, caret: u32) -> JsValue {
+    let caret_byte = offsets::utf16_to_byte_clamped(original, caret as usize);
+    let edits: Vec<diff::TextEdit> = diff::CaretSafeDiff::new(caret_byte)
+        .calculate(original, modified)
+        .edits()
+        .iter()
+        .filter_map(|e| e.to_unit(original, offsets::OffsetUnit::Utf16))
+        .collect();
+    serde_wasm_bindgen::to_value(&edits).unwrap_or(JsValue::NULL)
+}
+
+/// Process-wide tone ("None" / "Casual" / "Professional"); false for an unknown target
+#[wasm_bindgen]
+pub fn set_tone(enabled: bool, target: &str) -> bool {
+    match workers::tone::ToneTarget::from_name(target) {
+        Some(target) => {
+            workers::tone::set_global_tone(workers::tone::ToneSettings { enabled, target });
+            true
+        }
+        None => false,
+    }
+}
+
+/// Report the keyboard layout ("QWERTY" / "QWERTZ" / "AZERTY" / "Dvorak"); running engines and
+/// schedulers switch on their next tick. False for an unknown name
+#[wasm_bindgen]
+pub fn set_keyboard_layout(name: &str) -> bool {
+    match typo_model::KeyboardLayout::from_name(name) {
+        Some(layout) => {
+            typo_model::set_global_layout(layout);
+            true
+        }
+        None => false,
+    }
+}
+
 #[wasm_bindgen]
 pub struct WasmPauseTimer {
     timer: PauseTimer,
@@ -61,6 +107,7 @@ impl WasmPauseTimer {
 }
 
 #[wasm_bindgen]
+#[derive(Default)]
 pub struct WasmFragmentExtractor {
     extractor: FragmentExtractor,
 }
@@ -77,6 +124,21 @@ impl WasmFragmentExtractor {
     pub fn extract_fragment(&self, text: &str) -> Option<String> {
         self.extractor.extract_fragment(text).map(|s| s.to_string())
     }
+
+    /// `Fragment` with `range`/`context` in UTF-16 code units, or `null`
+    pub fn extract_at(&self, text: &str, caret: u32) -> JsValue {
+        let caret = offsets::utf16_to_byte_clamped(text, caret as usize);
+        let to_utf16 = |r: std::ops::Range<usize>| {
+            offsets::byte_to_utf16(text, r.start).unwrap_or(0)..offsets::byte_to_utf16(text, r.end).unwrap_or(0)
+        };
+        match self.extractor.extract(text, caret) {
+            Some(f) => {
+                let fragment = fragment::Fragment { range: to_utf16(f.range), context: to_utf16(f.context) };
+                serde_wasm_bindgen::to_value(&fragment).unwrap_or(JsValue::NULL)
+            }
+            None => JsValue::NULL,
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -93,8 +155,19 @@ impl WasmMerger {
         }
     }
 
-    pub fn apply_token(&mut self, token: &str) {
-        self.merger.apply_token(&token.to_string());
+    /// Returns the emitted `MergeBatch`, or `null` when none fired
+    pub fn apply_token(&mut self, token: &str) -> JsValue {
+        match self.merger.apply_token(&token.to_string()) {
+            Some(batch) => serde_wasm_bindgen::to_value(&batch).unwrap_or(JsValue::NULL),
+            None => JsValue::NULL,
+        }
+    }
+
+    pub fn finish(&mut self) -> JsValue {
+        match self.merger.finish() {
+            Some(batch) => serde_wasm_bindgen::to_value(&batch).unwrap_or(JsValue::NULL),
+            None => JsValue::NULL,
+        }
     }
 
     pub fn get_result(&self) -> String {
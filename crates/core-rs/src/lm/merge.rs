@@ -14,16 +14,19 @@
   • WHY  ▸ REQ-STREAMED-DIFFUSION
   • HOW  ▸ See linked contracts and guides in docs
 */
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   S T U B  ░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Stub for lm/$f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real implementation
-*/
 
-#[allow(dead_code)]
-pub fn _stub_lm_merge() {}
+use crate::llm::TokenStream;
+use crate::merge::{MergeBatch, Merger};
+
+/// Drive `merger` from any token stream until it ends
+///
+/// Mirrors `streamMerge` in `core/lm/mergePolicy.ts`: batches are emitted
+/// every ≤ N tokens or at boundaries, then once more when the stream closes.
+pub async fn stream_merge<S: TokenStream + ?Sized>(stream: &mut S, merger: &mut Merger) -> Vec<MergeBatch> {
+    let mut batches = Vec::new();
+    while let Some(token) = stream.next_token().await {
+        batches.extend(merger.apply_token(&token));
+    }
+    batches.extend(merger.finish());
+    batches
+}
@@ -1,31 +1,313 @@
+use crate::diff::{DiffCalculator, DiffOp, TextEdit};
 use crate::llm::Token;
 use log::debug;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How consecutive tokens are joined into the proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenJoin {
+    /// Tokens are whole words; separate them with a single space
+    #[default]
+    Space,
+    /// Tokens carry their own whitespace (BPE-style); concatenate verbatim
+    Concat,
+}
+
+pub struct MergeConfig {
+    /// Emit a batch at least every N tokens (spec: ≤ 4)
+    pub batch_tokens: usize,
+    /// Also emit as soon as the proposal ends on a word/punctuation boundary
+    pub flush_on_boundary: bool,
+    pub join: TokenJoin,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            batch_tokens: 4,
+            flush_on_boundary: true,
+            join: TokenJoin::Space,
+        }
+    }
+}
+
+/// Full set of operations that turns the original band into the current merge
+///
+/// Every batch is relative to the original text, so applying any batch (or
+/// the same batch twice) to the original yields the same result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeBatch {
+    pub sequence: u32,
+    pub operations: Vec<DiffOp>,
+    pub tokens_consumed: usize,
+    pub is_final: bool,
+}
+
+/// Where the band lived in the document when the merge started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandAnchor {
+    /// Byte offset of the band in the document (rebased over earlier user edits)
+    pub start: usize,
+    /// Last document revision the merger has seen
+    pub revision: u64,
+    /// FNV-1a hash of the original band text
+    pub fingerprint: u64,
+}
+
+/// Why pending merge output was discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "code")]
+pub enum StaleReason {
+    /// A user edit overlapped the band while the correction was in flight
+    BandEdited,
+    /// The document text at the anchored band no longer matches the original
+    FingerprintMismatch,
+    /// The host skipped reporting edits between revisions
+    RevisionMismatch { expected: u64, actual: u64 },
+}
+
+impl StaleReason {
+    pub fn code(&self) -> &'static str {
+        match self {
+            StaleReason::BandEdited => "BAND_EDITED",
+            StaleReason::FingerprintMismatch => "FINGERPRINT_MISMATCH",
+            StaleReason::RevisionMismatch { .. } => "REVISION_MISMATCH",
+        }
+    }
+}
+
+/// FNV-1a 64-bit hash used to fingerprint band text
+pub fn fingerprint(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
 
 pub struct Merger {
+    anchor: BandAnchor,
+    stale: Option<StaleReason>,
+    original: String,
+    proposal: String,
     buffer: String,
+    config: MergeConfig,
+    tokens: usize,
+    tokens_since_emit: usize,
+    sequence: u32,
+    last_emitted: Option<Vec<DiffOp>>,
+    /// Word-end offsets in the original (with 0) where the streamed prefix may stop
+    cuts: Vec<usize>,
+    /// Index into `cuts` of the last alignment; later ones search near it
+    cut: usize,
 }
 
+/// Word ends past the previous cut a single token may advance the alignment
+const CUT_LOOKAHEAD: usize = 4;
+
 impl Merger {
     pub fn new(initial_text: &str) -> Self {
+        Self::with_config(initial_text, MergeConfig::default())
+    }
+
+    /// Merger for a band at byte `start` of the document at `revision`
+    pub fn anchored(band_text: &str, start: usize, revision: u64) -> Self {
+        Self::anchored_with(band_text, start, revision, MergeConfig::default())
+    }
+
+    /// [`Merger::anchored`] with an explicit config
+    pub fn anchored_with(band_text: &str, start: usize, revision: u64, config: MergeConfig) -> Self {
+        let mut merger = Self::with_config(band_text, config);
+        merger.anchor.start = start;
+        merger.anchor.revision = revision;
+        merger
+    }
+
+    pub fn with_config(initial_text: &str, config: MergeConfig) -> Self {
         debug!("Initializing Merger with text: '{}'", initial_text);
+        let mut cuts = vec![0];
+        cuts.extend(
+            initial_text
+                .split_word_bound_indices()
+                .filter(|(_, w)| !w.trim().is_empty())
+                .map(|(i, w)| i + w.len()),
+        );
+        cuts.dedup();
         Self {
+            anchor: BandAnchor { start: 0, revision: 0, fingerprint: fingerprint(initial_text) },
+            stale: None,
+            original: initial_text.to_string(),
+            proposal: String::new(),
             buffer: initial_text.to_string(),
+            config,
+            tokens: 0,
+            tokens_since_emit: 0,
+            sequence: 0,
+            last_emitted: None,
+            cuts,
+            cut: 0,
         }
     }
 
-    pub fn apply_token(&mut self, token: &Token) {
+    /// Feed one LM token; returns a batch when the emission rule fires
+    pub fn apply_token(&mut self, token: &Token) -> Option<MergeBatch> {
         debug!("Applying token: '{}'", token);
-        // For now, we just append with a space.
-        // A real implementation would involve diffing.
-        if !self.buffer.is_empty() {
-            self.buffer.push(' ');
+        if self.stale.is_some() {
+            return None;
+        }
+        if self.config.join == TokenJoin::Space && !self.proposal.is_empty() {
+            self.proposal.push(' ');
+        }
+        self.proposal.push_str(token);
+        self.tokens += 1;
+        self.tokens_since_emit += 1;
+
+        let ops = self.aligned_ops(false);
+        self.buffer = DiffCalculator::apply_diff(&self.original, &ops);
+
+        let at_boundary = self.config.flush_on_boundary
+            && self.proposal.chars().last().is_some_and(is_boundary_char);
+        if at_boundary || self.tokens_since_emit >= self.config.batch_tokens.max(1) {
+            return self.emit(ops, false);
+        }
+        None
+    }
+
+    /// Close the stream: the proposal now replaces the whole band
+    ///
+    /// Always yields a final batch once any token was seen.
+    pub fn finish(&mut self) -> Option<MergeBatch> {
+        if self.tokens == 0 || self.stale.is_some() {
+            return None;
         }
-        self.buffer.push_str(token);
+        let ops = self.aligned_ops(true);
+        self.buffer = DiffCalculator::apply_diff(&self.original, &ops);
+        self.emit(ops, true)
     }
 
+    /// Original band merged with everything streamed so far
     pub fn get_result(&self) -> &str {
         &self.buffer
     }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    pub fn proposal(&self) -> &str {
+        &self.proposal
+    }
+
+    pub fn anchor(&self) -> &BandAnchor {
+        &self.anchor
+    }
+
+    /// Set once the band was invalidated; no further batches are emitted
+    pub fn stale_reason(&self) -> Option<StaleReason> {
+        self.stale
+    }
+
+    /// Track a user edit (byte range in the document before the edit)
+    ///
+    /// Edits before the band shift it; edits after it are ignored; edits
+    /// touching it make the merger stale.
+    pub fn observe_user_edit(&mut self, edit: &TextEdit, revision: u64) -> Result<(), StaleReason> {
+        if let Some(reason) = self.stale {
+            return Err(reason);
+        }
+        let band_start = self.anchor.start;
+        let band_end = band_start + self.original.len();
+        let (start, end) = (edit.range.start, edit.range.end);
+
+        if end <= band_start {
+            self.anchor.start = band_start - (end - start) + edit.replacement.len();
+        } else if start < band_end {
+            debug!("Merger band [{}, {}) invalidated by user edit {:?}", band_start, band_end, edit.range);
+            return Err(self.mark_stale(StaleReason::BandEdited));
+        }
+        self.anchor.revision = revision;
+        Ok(())
+    }
+
+    /// Translate a batch into document edits, verifying the band is untouched
+    pub fn rebase(&mut self, batch: &MergeBatch, document: &str, revision: u64) -> Result<Vec<TextEdit>, StaleReason> {
+        if let Some(reason) = self.stale {
+            return Err(reason);
+        }
+        if revision != self.anchor.revision {
+            return Err(self.mark_stale(StaleReason::RevisionMismatch { expected: self.anchor.revision, actual: revision }));
+        }
+        let band = document.get(self.anchor.start..self.anchor.start + self.original.len());
+        if band.map(fingerprint) != Some(self.anchor.fingerprint) {
+            return Err(self.mark_stale(StaleReason::FingerprintMismatch));
+        }
+        Ok(DiffCalculator::to_edits(&batch.operations)
+            .into_iter()
+            .map(|e| TextEdit::new(e.range.start + self.anchor.start..e.range.end + self.anchor.start, e.replacement))
+            .collect())
+    }
+
+    fn mark_stale(&mut self, reason: StaleReason) -> StaleReason {
+        self.stale = Some(reason);
+        reason
+    }
+
+    fn emit(&mut self, operations: Vec<DiffOp>, is_final: bool) -> Option<MergeBatch> {
+        self.tokens_since_emit = 0;
+        // The final batch always goes out so hosts get a definite end state
+        if !is_final && self.last_emitted.as_ref() == Some(&operations) {
+            return None;
+        }
+        self.last_emitted = Some(operations.clone());
+        self.sequence += 1;
+        debug!("Merger emitting batch {} ({} ops)", self.sequence, operations.len());
+        Some(MergeBatch {
+            sequence: self.sequence,
+            operations,
+            tokens_consumed: self.tokens,
+            is_final,
+        })
+    }
+
+    /// Diff the proposal against the prefix of the original it most likely
+    /// covers; the not-yet-streamed tail of the original is kept.
+    fn aligned_ops(&mut self, complete: bool) -> Vec<DiffOp> {
+        let cut = if complete { self.original.len() } else { self.best_cut() };
+        let mut ops = DiffCalculator::calculate_diff(&self.original[..cut], &self.proposal);
+        if cut < self.original.len() {
+            let tail = &self.original[cut..];
+            match ops.last_mut() {
+                Some(DiffOp::Keep(prev)) => prev.push_str(tail),
+                _ => ops.push(DiffOp::Keep(tail.to_string())),
+            }
+        }
+        ops
+    }
+
+    /// Word-end offset in the original whose prefix is cheapest to turn into the proposal.
+    /// The proposal only grows, so only word ends just before and a few after the
+    /// previous alignment are diffed, keeping each token's cost independent of band length.
+    fn best_cut(&mut self) -> usize {
+        let last = self.cuts.len() - 1;
+        let mut best = (usize::MAX, self.cut);
+        for i in self.cut.saturating_sub(1)..=(self.cut + CUT_LOOKAHEAD).min(last) {
+            let cost = edit_cost(&DiffCalculator::calculate_diff(&self.original[..self.cuts[i]], &self.proposal));
+            if cost < best.0 {
+                best = (cost, i);
+            }
+        }
+        self.cut = best.1;
+        self.cuts[self.cut]
+    }
+}
+
+fn edit_cost(ops: &[DiffOp]) -> usize {
+    ops.iter()
+        .filter(|op| op.is_edit())
+        .map(|op| op.original_text().graphemes(true).count() + op.modified_text().graphemes(true).count())
+        .sum()
+}
+
+/// Same boundary set as the TS `streamMerge`
+fn is_boundary_char(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | '—' | '"' | '\'' | '”' | '’' | ')' | ']' | '}')
 }
 
 #[cfg(test)]
@@ -34,11 +316,17 @@ mod tests {
 
     #[test]
     fn test_merger() {
-        let mut merger = Merger::new("Initial text.");
-        merger.apply_token(&"Here".to_string());
-        merger.apply_token(&"is".to_string());
-        merger.apply_token(&"more.".to_string());
+        let mut merger = Merger::new("Teh cat sat on teh mat.");
+        merger.apply_token(&"The".to_string());
+        assert_eq!(merger.get_result(), "The cat sat on teh mat.");
+        merger.apply_token(&"cat".to_string());
+        merger.apply_token(&"sat".to_string());
+        merger.apply_token(&"on".to_string());
+        merger.apply_token(&"the".to_string());
+        assert_eq!(merger.get_result(), "The cat sat on the mat.");
+        merger.apply_token(&"mat.".to_string());
+        merger.finish();
 
-        assert_eq!(merger.get_result(), "Initial text. Here is more.");
+        assert_eq!(merger.get_result(), "The cat sat on the mat.");
     }
-} 
\ No newline at end of file
+}
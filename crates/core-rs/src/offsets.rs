@@ -0,0 +1,143 @@
+/*╔══════════════════════════════════════════════════════════╗
+  ║  ░  OFFSETS.RS  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                            ║
+  ║   Byte ↔ UTF-16 ↔ scalar ↔ grapheme offset conversion.     ║
+  ║                                                            ║
+  ╚══════════════════════════════════════════════════════════╝
+  • WHAT ▸ Translate positions between Rust (UTF-8) and host units
+  • WHY  ▸ Swift NSRange and DOM selections count UTF-16 code units
+  • HOW  ▸ Linear scans; offsets that split a unit are rejected (None)
+*/
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Unit a text offset is counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OffsetUnit {
+    /// UTF-8 bytes (Rust `str` indices)
+    Utf8,
+    /// UTF-16 code units (JS strings, NSString)
+    Utf16,
+    /// Unicode scalar values (Rust `char`s)
+    Scalar,
+    /// Extended grapheme clusters
+    Grapheme,
+}
+
+/// Convert a byte offset into `unit`; `None` if it is out of range or not on a unit boundary
+pub fn from_byte(text: &str, byte: usize, unit: OffsetUnit) -> Option<usize> {
+    if byte > text.len() || !text.is_char_boundary(byte) {
+        return None;
+    }
+    match unit {
+        OffsetUnit::Utf8 => Some(byte),
+        OffsetUnit::Utf16 => Some(text[..byte].encode_utf16().count()),
+        OffsetUnit::Scalar => Some(text[..byte].chars().count()),
+        OffsetUnit::Grapheme => {
+            if byte == text.len() {
+                return Some(text.graphemes(true).count());
+            }
+            text.grapheme_indices(true).position(|(i, _)| i == byte)
+        }
+    }
+}
+
+/// Convert an offset counted in `unit` into a byte offset
+pub fn to_byte(text: &str, offset: usize, unit: OffsetUnit) -> Option<usize> {
+    match unit {
+        OffsetUnit::Utf8 => (offset <= text.len() && text.is_char_boundary(offset)).then_some(offset),
+        OffsetUnit::Utf16 => {
+            let mut units = 0usize;
+            for (i, c) in text.char_indices() {
+                if units == offset {
+                    return Some(i);
+                }
+                units += c.len_utf16();
+                if units > offset {
+                    // Offset points into the middle of a surrogate pair
+                    return None;
+                }
+            }
+            (units == offset).then_some(text.len())
+        }
+        OffsetUnit::Scalar => text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .nth(offset),
+        OffsetUnit::Grapheme => text
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .nth(offset),
+    }
+}
+
+/// Convert an offset between any two units
+pub fn convert(text: &str, offset: usize, from: OffsetUnit, to: OffsetUnit) -> Option<usize> {
+    from_byte(text, to_byte(text, offset, from)?, to)
+}
+
+/// Convert a range between any two units
+pub fn convert_range(text: &str, range: Range<usize>, from: OffsetUnit, to: OffsetUnit) -> Option<Range<usize>> {
+    let start = convert(text, range.start, from, to)?;
+    let end = convert(text, range.end, from, to)?;
+    (start <= end).then_some(start..end)
+}
+
+/// Length of `text` in `unit`
+pub fn len_in(text: &str, unit: OffsetUnit) -> usize {
+    match unit {
+        OffsetUnit::Utf8 => text.len(),
+        OffsetUnit::Utf16 => text.encode_utf16().count(),
+        OffsetUnit::Scalar => text.chars().count(),
+        OffsetUnit::Grapheme => text.graphemes(true).count(),
+    }
+}
+
+/// UTF-16 offset → byte offset
+pub fn utf16_to_byte(text: &str, offset: usize) -> Option<usize> {
+    to_byte(text, offset, OffsetUnit::Utf16)
+}
+
+/// Byte offset → UTF-16 offset
+pub fn byte_to_utf16(text: &str, byte: usize) -> Option<usize> {
+    from_byte(text, byte, OffsetUnit::Utf16)
+}
+
+/// UTF-16 offset → byte offset, clamped to the text and snapped back to a char boundary
+///
+/// Hosts occasionally report carets past the end or inside a surrogate pair
+/// during rapid edits; this never fails so callers can stay infallible.
+pub fn utf16_to_byte_clamped(text: &str, offset: usize) -> usize {
+    let mut units = 0usize;
+    for (i, c) in text.char_indices() {
+        units += c.len_utf16();
+        if units > offset {
+            return i;
+        }
+    }
+    text.len()
+}
+
+/// Byte offset clamped to the text and snapped back to a char boundary
+pub(crate) fn floor_char_boundary(text: &str, byte: usize) -> usize {
+    let mut i = byte.min(text.len());
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+/// Byte offset clamped to the text and snapped forward to a char boundary
+pub(crate) fn ceil_char_boundary(text: &str, byte: usize) -> usize {
+    let mut i = byte.min(text.len());
+    while !text.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
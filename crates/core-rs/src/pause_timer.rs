@@ -1,33 +1,38 @@
-use chrono::{DateTime, Utc};
+use crate::clock::{self, Clock};
 use log::info;
-use std::time::Duration;
+use std::sync::Arc;
 
 pub struct PauseTimer {
-    idle_threshold: Duration,
-    last_activity: DateTime<Utc>,
+    idle_threshold_ms: u64,
+    last_activity_ms: u64,
+    clock: Arc<dyn Clock>,
 }
 
 impl PauseTimer {
     pub fn new(idle_threshold_ms: u64) -> Self {
+        Self::with_clock(idle_threshold_ms, clock::system())
+    }
+
+    pub fn with_clock(idle_threshold_ms: u64, clock: Arc<dyn Clock>) -> Self {
         info!("Initializing PauseTimer with threshold {}ms", idle_threshold_ms);
         Self {
-            idle_threshold: Duration::from_millis(idle_threshold_ms),
-            last_activity: Utc::now(),
+            idle_threshold_ms,
+            last_activity_ms: clock.now_ms(),
+            clock,
         }
     }
 
     pub fn record_activity(&mut self) {
-        self.last_activity = Utc::now();
+        self.last_activity_ms = self.clock.now_ms();
     }
 
     pub fn is_paused(&self) -> bool {
-        let now = Utc::now();
-        let idle_time = now.signed_duration_since(self.last_activity);
-        
-        let is_paused = idle_time > chrono::Duration::from_std(self.idle_threshold).unwrap();
+        let idle_time = self.clock.now_ms().saturating_sub(self.last_activity_ms);
+
+        let is_paused = idle_time > self.idle_threshold_ms;
         if is_paused {
-            log::debug!("Paused. Idle time: {}ms", idle_time.num_milliseconds());
+            log::debug!("Paused. Idle time: {}ms", idle_time);
         }
         is_paused
     }
-} 
\ No newline at end of file
+}
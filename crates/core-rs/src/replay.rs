@@ -0,0 +1,201 @@
+/*╔══════════════════════════════════════════════════════════╗
+  ║  ░  REPLAY.RS  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                            ║
+  ║   Session capture and deterministic replay.                ║
+  ║                                                            ║
+  ╚══════════════════════════════════════════════════════════╝
+  • WHAT ▸ JSONL traces of caret events, text snapshots and ticks
+  • WHY  ▸ Turn user bug reports into reproducible golden tests
+  • HOW  ▸ Replayer drives CaretMonitor/Engine with a ManualClock;
+           every caret in a trace is in UTF-16 code units
+*/
+
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::caret_monitor::{CaretEvent, CaretMonitor, CaretSnapshot, DeviceTier, Thresholds};
+use crate::clock::ManualClock;
+use crate::offsets::utf16_to_byte_clamped;
+use crate::diff::TextEdit;
+use crate::engine::{Engine, SkipReason};
+
+/// Trace format version written in the header line; version 1 traces
+/// stored `Text` carets as byte offsets
+pub const TRACE_VERSION: u32 = 2;
+
+/// One line of a JSONL session trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceRecord {
+    Header { version: u32 },
+    /// Host caret/input event, exactly as fed to `CaretMonitor::update`
+    Event { event: CaretEvent },
+    /// Full text and caret at a point in time; the caret is in UTF-16 code
+    /// units, like `CaretEvent::caret`
+    Text { timestamp_ms: u64, text: String, caret: usize },
+    /// The host asked the engine to run
+    Tick { timestamp_ms: u64 },
+}
+
+/// Writes a session trace as JSON lines
+pub struct SessionRecorder<W: Write> {
+    writer: W,
+    record_text: bool,
+    wrote_header: bool,
+}
+
+impl<W: Write> SessionRecorder<W> {
+    /// Recorder for events and ticks only (no document text is captured)
+    pub fn new(writer: W) -> Self {
+        Self { writer, record_text: false, wrote_header: false }
+    }
+
+    /// Recorder that also captures text snapshots; only use with user consent
+    pub fn with_text(writer: W) -> Self {
+        Self { writer, record_text: true, wrote_header: false }
+    }
+
+    pub fn record_event(&mut self, event: &CaretEvent) -> io::Result<()> {
+        self.write(&TraceRecord::Event { event: event.clone() })
+    }
+
+    /// `caret` is in UTF-16 code units. Ignored unless the recorder was
+    /// created with `with_text`
+    pub fn record_text(&mut self, timestamp_ms: u64, text: &str, caret: usize) -> io::Result<()> {
+        if !self.record_text {
+            return Ok(());
+        }
+        self.write(&TraceRecord::Text { timestamp_ms, text: text.to_string(), caret })
+    }
+
+    pub fn record_tick(&mut self, timestamp_ms: u64) -> io::Result<()> {
+        self.write(&TraceRecord::Tick { timestamp_ms })
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write(&mut self, record: &TraceRecord) -> io::Result<()> {
+        if !self.wrote_header {
+            self.wrote_header = true;
+            self.write(&TraceRecord::Header { version: TRACE_VERSION })?;
+        }
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+/// Engine result for one replayed tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTick {
+    pub timestamp_ms: u64,
+    pub edits: Vec<TextEdit>,
+    pub skipped: Option<SkipReason>,
+}
+
+/// Everything a replay produced, in order, for golden comparison
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ReplayOutput {
+    pub snapshots: Vec<CaretSnapshot>,
+    pub ticks: Vec<ReplayTick>,
+}
+
+/// Feeds a recorded trace back through the core
+pub struct SessionReplayer {
+    records: Vec<TraceRecord>,
+}
+
+impl SessionReplayer {
+    pub fn new(records: Vec<TraceRecord>) -> Self {
+        Self { records }
+    }
+
+    /// Parse a JSONL trace; blank lines are skipped
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut records = Vec::new();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: TraceRecord = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("trace line {}: {}", n + 1, e))
+            })?;
+            if let TraceRecord::Header { version } = record {
+                if version > TRACE_VERSION {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("trace version {} is newer than supported {}", version, TRACE_VERSION),
+                    ));
+                }
+            }
+            records.push(record);
+        }
+        Ok(Self { records })
+    }
+
+    pub fn records(&self) -> &[TraceRecord] {
+        &self.records
+    }
+
+    /// Replay events through a fresh `CaretMonitor`; ticks flush at their timestamp
+    pub fn replay_monitor(&self, thresholds: Thresholds) -> Vec<CaretSnapshot> {
+        let mut monitor = CaretMonitor::new(thresholds, DeviceTier::Native);
+        let mut snapshots = Vec::new();
+        for record in &self.records {
+            match record {
+                TraceRecord::Event { event } => {
+                    monitor.update(event.clone());
+                }
+                TraceRecord::Tick { timestamp_ms } => {
+                    monitor.flush(*timestamp_ms);
+                }
+                _ => {}
+            }
+            snapshots.extend(monitor.drain_snapshots());
+        }
+        snapshots
+    }
+
+    /// Replay the whole trace through `engine` on a virtual clock; the
+    /// engine's own clock is restored afterwards
+    pub fn replay(&self, engine: &mut Engine) -> ReplayOutput {
+        let clock = ManualClock::new(0);
+        let previous = engine.clock();
+        engine.set_clock(Arc::new(clock.clone()));
+
+        let mut output = ReplayOutput::default();
+        let mut text = String::new();
+        let mut caret = 0usize;
+        let mut byte_carets = false;
+        for record in &self.records {
+            match record {
+                TraceRecord::Header { version } => byte_carets = *version < 2,
+                TraceRecord::Event { event } => {
+                    clock.set(event.timestamp_ms);
+                    engine.observe(event.clone());
+                }
+                TraceRecord::Text { timestamp_ms, text: t, caret: c } => {
+                    clock.set(*timestamp_ms);
+                    text.clone_from(t);
+                    caret = if byte_carets { *c } else { utf16_to_byte_clamped(&text, *c) };
+                }
+                TraceRecord::Tick { timestamp_ms } => {
+                    clock.set(*timestamp_ms);
+                    let out = engine.tick(&text, caret);
+                    output.ticks.push(ReplayTick {
+                        timestamp_ms: *timestamp_ms,
+                        edits: out.edits,
+                        skipped: out.diagnostics.skipped,
+                    });
+                }
+            }
+            output.snapshots.extend(engine.drain_snapshots());
+        }
+        engine.set_clock(previous);
+        output
+    }
+}
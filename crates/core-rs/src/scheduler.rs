@@ -1,50 +1,146 @@
-/*╔══════════════════════════════════════════════════════════╗
-  ║  ░  SCHEDULER.RS  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
-  ║                                                            ║
-  ║                                                            ║
-  ║                                                            ║
-  ║                                                            ║
-  ║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-  ║                                                            ║
-  ║                                                            ║
-  ║                                                            ║
-  ║                                                            ║
-  ╚══════════════════════════════════════════════════════════╝
-  • WHAT ▸ Integrate Noise → Context → Tone pipeline with staging buffer; English-only gating for full pipeline (Noise for others)
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  S W E E P   S C H E D U L E R  ░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Noise while typing, Context on short pause, Tone on long.  ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Run the Noise → Context → Tone pipeline over a text
+           snapshot, English-only gating for Context/Tone (Noise
+           for every language); edits are returned directly, the
+           staging buffer and confidence gate are left to callers
   • WHY  ▸ REQ-THREE-STAGE-PIPELINE, REQ-LANGUAGE-GATING
-  • HOW  ▸ See linked contracts and guides in docs
+  • HOW  ▸ Idle time picks the due stages; each stage runs on the
+           previous stage's output; Context/Tone only edit inside
+           English language runs, so non-English text gets Noise only
 */
 
-use std::time::{Duration, Instant};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::caret_monitor::Thresholds;
+use crate::clock::{self, Clock};
+use crate::diff::TextEdit;
+use crate::language_detection::{self, Language, LanguageDetector, LanguageRun};
+use crate::offsets::floor_char_boundary;
+use crate::typo_model::{self, KeyboardLayout};
+use crate::workers::context::ContextWorker;
+use crate::workers::noise::{NoiseInput, NoiseRegistry};
+use crate::workers::tone::{self, ToneSettings, ToneWorker};
+
+/// Pipeline stages in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    /// Typo and spacing fixes; runs while typing
+    Noise,
+    /// Sentence-level repairs; runs from the short pause
+    Context,
+    /// Tone rewrites; runs from the long pause when tone is enabled
+    Tone,
+}
+
+/// An edit produced by one stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageEdit {
+    pub stage: Stage,
+    /// Byte range of the text produced by the edits before it
+    pub edit: TextEdit,
+    pub confidence: f32,
+}
+
+/// Result of one sweep
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepOutput {
+    /// Language of the run at the caret
+    pub language: Language,
+    /// Language runs of the input text
+    pub runs: Vec<LanguageRun>,
+    /// Stages that ran, in order; empty when nothing was due
+    pub stages: Vec<Stage>,
+    /// Apply in order; each edit is relative to the text after the previous ones
+    pub edits: Vec<StageEdit>,
+    /// Text after every stage
+    pub text: String,
+    /// Byte caret in `text`
+    pub caret: usize,
+    /// Context/Tone were due but skipped, or had proposals dropped, because
+    /// the text they touch is not English
+    pub gated: bool,
+}
+
+impl SweepOutput {
+    fn idle(text: &str, caret: usize, runs: Vec<LanguageRun>) -> Self {
+        let language = language_detection::run_at(&runs, caret).map_or(Language::Unknown, |r| r.language.clone());
+        Self { language, runs, stages: Vec::new(), edits: Vec::new(), text: text.to_string(), caret, gated: false }
+    }
+}
 
 /// Sweep scheduler for coordinating pipeline operations
 pub struct SweepScheduler {
-    /// Last activity timestamp
-    pub last_activity: Instant,
-    /// Pause threshold duration
+    /// Last activity timestamp (clock milliseconds)
+    pub last_activity_ms: u64,
+    /// Short pause: Context joins the sweep
     pub pause_threshold: Duration,
+    /// Long pause: Tone joins the sweep
+    pub long_pause_threshold: Duration,
     /// Whether scheduler is active
     pub is_active: bool,
+    pub noise: NoiseRegistry,
+    pub context: ContextWorker,
+    pub detector: LanguageDetector,
+    tone_worker: ToneWorker,
+    tone: Option<ToneSettings>,
+    language: Option<Language>,
+    keyboard_layout: KeyboardLayout,
+    /// Global layout report last adopted (see `typo_model::global_layout_report`)
+    layout_report: u64,
+    /// Text, caret and last stage of the previous sweep
+    last_run: Option<(String, usize, Stage)>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SweepScheduler {
     /// Create a new sweep scheduler
     pub fn new(pause_threshold_ms: u64) -> Self {
+        Self::with_clock(pause_threshold_ms, clock::system())
+    }
+
+    /// Create a scheduler reading time from `clock`
+    pub fn with_clock(pause_threshold_ms: u64, clock: Arc<dyn Clock>) -> Self {
+        let long_pause_ms = Thresholds::default().long_pause_ms.max(pause_threshold_ms);
         Self {
-            last_activity: Instant::now(),
+            last_activity_ms: clock.now_ms(),
             pause_threshold: Duration::from_millis(pause_threshold_ms),
+            long_pause_threshold: Duration::from_millis(long_pause_ms),
             is_active: false,
+            noise: NoiseRegistry::new(),
+            context: ContextWorker::default(),
+            detector: LanguageDetector::new(),
+            tone_worker: ToneWorker::default(),
+            tone: None,
+            language: None,
+            keyboard_layout: KeyboardLayout::Qwerty,
+            layout_report: typo_model::global_layout_report().0,
+            last_run: None,
+            clock,
         }
     }
 
+    /// Scheduler using the caret monitor's short/long pause thresholds
+    pub fn from_thresholds(thresholds: Thresholds, clock: Arc<dyn Clock>) -> Self {
+        let mut scheduler = Self::with_clock(thresholds.short_pause_ms, clock);
+        scheduler.long_pause_threshold = Duration::from_millis(thresholds.long_pause_ms.max(thresholds.short_pause_ms));
+        scheduler
+    }
+
     /// Record user activity
     pub fn record_activity(&mut self) {
-        self.last_activity = Instant::now();
+        self.last_activity_ms = self.clock.now_ms();
     }
 
     /// Check if user is currently paused
     pub fn is_paused(&self) -> bool {
-        self.last_activity.elapsed() >= self.pause_threshold
+        self.idle() >= self.pause_threshold
     }
 
     /// Start the scheduler
@@ -58,14 +154,154 @@ impl SweepScheduler {
         self.is_active = false;
     }
 
-    /// Execute a sweep cycle
-    pub fn tick(&mut self) -> bool {
+    /// Tone settings in effect (override, else the global setting)
+    pub fn tone(&self) -> ToneSettings {
+        self.tone.unwrap_or_else(tone::global_tone)
+    }
+
+    /// Override the tone for this scheduler; `None` reverts to the global setting
+    pub fn set_tone(&mut self, settings: Option<ToneSettings>) {
+        self.tone = settings;
+    }
+
+    /// Layout the noise rules score keyboard typos against
+    pub fn keyboard_layout(&self) -> KeyboardLayout {
+        self.keyboard_layout
+    }
+
+    /// Switch layouts for every noise rule that scores keyboard typos
+    pub fn set_keyboard_layout(&mut self, layout: KeyboardLayout) {
+        self.keyboard_layout = layout;
+        self.noise.set_keyboard_layout(layout);
+        self.last_run = None;
+    }
+
+    /// Adopt a layout the host reported since this scheduler last looked
+    fn follow_host_layout(&mut self) {
+        let (report, layout) = typo_model::global_layout_report();
+        if report != self.layout_report {
+            self.layout_report = report;
+            self.set_keyboard_layout(layout);
+        }
+    }
+
+    /// Pin the document language (e.g. from the host's `lang` attribute); `None` detects it
+    pub fn set_language(&mut self, language: Option<Language>) {
+        self.language = language;
+    }
+
+    /// Language of the whole text; detections below the detector's threshold count as `Unknown`
+    pub fn language_for(&self, text: &str) -> Language {
+        if let Some(language) = &self.language {
+            return language.clone();
+        }
+        self.detector.detect(text).0
+    }
+
+    /// Language runs used for gating; a pinned language covers the whole text
+    pub fn language_runs(&self, text: &str) -> Vec<LanguageRun> {
+        match &self.language {
+            Some(language) => vec![LanguageRun { range: 0..text.len(), language: language.clone(), confidence: 1.0 }],
+            None => self.detector.segment(text),
+        }
+    }
+
+    /// Language of the run the caret is typing in
+    pub fn language_at(&self, text: &str, caret: usize) -> Language {
+        let runs = self.language_runs(text);
+        language_detection::run_at(&runs, caret).map_or(Language::Unknown, |r| r.language.clone())
+    }
+
+    /// Stages due at the current idle time, before language gating
+    pub fn due_stages(&self) -> Vec<Stage> {
         if !self.is_active {
-            return false;
+            return Vec::new();
+        }
+        let idle = self.idle();
+        let mut stages = vec![Stage::Noise];
+        if idle >= self.pause_threshold {
+            stages.push(Stage::Context);
+        }
+        if idle >= self.long_pause_threshold && self.tone().is_active() {
+            stages.push(Stage::Tone);
+        }
+        stages
+    }
+
+    /// Execute a sweep cycle over `text` with the caret at byte `caret`
+    pub fn tick(&mut self, text: &str, caret: usize) -> SweepOutput {
+        self.follow_host_layout();
+        let caret = floor_char_boundary(text, caret);
+        let runs = self.language_runs(text);
+        let due = self.due_stages();
+        let Some(&last) = due.last() else {
+            return SweepOutput::idle(text, caret, runs);
+        };
+        if matches!(&self.last_run, Some((t, c, s)) if t == text && *c == caret && *s >= last) {
+            return SweepOutput::idle(text, caret, runs);
         }
 
-        // Stub implementation - returns whether processing occurred
-        !self.is_paused()
+        // REQ-LANGUAGE-GATING: Context and Tone need English text before the caret
+        let english = !english_ranges(&runs, caret).is_empty();
+        let stages: Vec<Stage> = due.iter().copied().filter(|s| english || *s == Stage::Noise).collect();
+        let mut out = SweepOutput { gated: stages.len() < due.len(), stages, ..SweepOutput::idle(text, caret, runs) };
+        for stage in out.stages.clone() {
+            let proposals: Vec<(TextEdit, f32)> = match stage {
+                Stage::Noise => {
+                    self.run_noise(&mut out);
+                    continue;
+                }
+                Stage::Context => {
+                    let result = self.context.propose(&out.text, out.caret, &Language::English);
+                    result.proposals.into_iter().map(|p| (p.edit, p.confidence)).collect()
+                }
+                Stage::Tone => {
+                    self.tone_worker.settings = self.tone();
+                    self.tone_worker.plan(&out.text, out.caret).into_iter().map(|p| (p.edit, p.confidence)).collect()
+                }
+            };
+            // Runs of the text this stage saw; each proposal must stay inside an English one
+            let allowed = english_ranges(&self.language_runs(&out.text), out.caret);
+            let before = proposals.len();
+            let proposals: Vec<(TextEdit, f32)> = proposals
+                .into_iter()
+                .filter(|(e, _)| allowed.iter().any(|r| r.start <= e.range.start && e.range.end <= r.end))
+                .collect();
+            out.gated |= proposals.len() < before;
+            apply_stage(stage, proposals, &mut out);
+        }
+        log::debug!("sweep {:?} ({}): {} edits", out.stages, out.language.code(), out.edits.len());
+        self.last_run = Some((text.to_string(), caret, last));
+        out
+    }
+
+    /// Noise proposals one at a time, each on the previous result (like `NoiseRegistry::sweep`).
+    /// The language-neutral rules see the whole text; each language run before the
+    /// caret then gets a pass with its own rule pack, confined to that run
+    fn run_noise(&self, out: &mut SweepOutput) {
+        self.noise_passes(out, &Language::Unknown, None);
+        let (runs, caret) = (self.language_runs(&out.text), out.caret);
+        // Rightmost first, so edits never shift a run still to come
+        for run in runs.iter().rev().filter(|r| r.range.start < caret && self.noise.packs.get(&r.language).is_some()) {
+            self.noise_passes(out, &run.language, Some(run.range.clone()));
+        }
+    }
+
+    fn noise_passes(&self, out: &mut SweepOutput, language: &Language, mut hint: Option<Range<usize>>) {
+        const MAX_PASSES: usize = 32;
+        for _ in 0..MAX_PASSES {
+            let mut input = NoiseInput::new(&out.text, out.caret).with_language(language.clone());
+            input.hint = hint.clone();
+            let Some(proposal) = self.noise.transform(&input) else { break };
+            if let Some(hint) = &mut hint {
+                hint.end = hint.end + proposal.edit.replacement.len() - proposal.edit.range.len();
+            }
+            apply_edit(Stage::Noise, proposal.edit, proposal.confidence, out);
+        }
+    }
+
+    fn idle(&self) -> Duration {
+        Duration::from_millis(self.clock.now_ms().saturating_sub(self.last_activity_ms))
     }
 }
 
@@ -73,4 +309,32 @@ impl Default for SweepScheduler {
     fn default() -> Self {
         Self::new(500) // Default 500ms pause threshold
     }
-}
\ No newline at end of file
+}
+
+/// Ranges of the English runs that start before the caret
+fn english_ranges(runs: &[LanguageRun], caret: usize) -> Vec<Range<usize>> {
+    runs.iter().filter(|r| r.language == Language::English && r.range.start < caret).map(|r| r.range.clone()).collect()
+}
+
+/// Apply proposals made against the same text; overlapping ones after the first are dropped
+fn apply_stage(stage: Stage, mut proposals: Vec<(TextEdit, f32)>, out: &mut SweepOutput) {
+    proposals.sort_by_key(|(e, _)| (e.range.start, e.range.end));
+    let mut cursor = 0;
+    proposals.retain(|(e, _)| {
+        let keep = e.range.start >= cursor && e.range.end <= out.caret;
+        if keep {
+            cursor = e.range.end.max(cursor);
+        }
+        keep
+    });
+    // Back to front so earlier ranges stay valid as each edit lands
+    for (edit, confidence) in proposals.into_iter().rev() {
+        apply_edit(stage, edit, confidence, out);
+    }
+}
+
+fn apply_edit(stage: Stage, edit: TextEdit, confidence: f32, out: &mut SweepOutput) {
+    out.text.replace_range(edit.range.clone(), &edit.replacement);
+    out.caret = out.caret - edit.range.len() + edit.replacement.len();
+    out.edits.push(StageEdit { stage, edit, confidence });
+}
@@ -0,0 +1,305 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  S T A G I N G   B U F F E R  ░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Proposals wait here until the gate lets them through.      ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Hold / commit / discard / rollback states for stage
+           proposals (port of core/stagingBuffer.ts)
+  • WHY  ▸ REQ-CONFIDENCE-GATE, REQ-THREE-STAGE-PIPELINE
+  • HOW  ▸ Proposals keyed by byte range; ranges are rebased as
+           commits land and user edits arrive; promotion scores
+           them with the confidence gate
+*/
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::clock::{self, Clock};
+use crate::confidence::{
+    apply_thresholds, compute_dynamic_thresholds, score_proposal, temporal_decay, ConfidenceConfig,
+    DynamicThresholdsInput, EditType, GateDecision, GateThresholds,
+};
+use crate::diff::TextEdit;
+use crate::scheduler::{Stage, StageEdit};
+
+pub type ProposalId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalState {
+    /// Waiting for more confidence (or for the caret to move on)
+    Hold,
+    /// Passed the gate; applied once handed out by `take_commits`
+    Commit,
+    /// Below the discard floor, or invalidated by a user edit
+    Discard,
+    /// Withdrawn before it landed, or undone after
+    Rollback,
+}
+
+impl From<GateDecision> for ProposalState {
+    fn from(decision: GateDecision) -> Self {
+        match decision {
+            GateDecision::Hold => ProposalState::Hold,
+            GateDecision::Commit => ProposalState::Commit,
+            GateDecision::Discard => ProposalState::Discard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub id: ProposalId,
+    pub stage: Stage,
+    /// Byte range in the current text; the replacement's span once applied
+    pub range: Range<usize>,
+    pub replacement: String,
+    /// Text the proposal replaces (restored by a rollback)
+    pub original: String,
+    pub confidence: f32,
+    pub created_ms: u64,
+    pub state: ProposalState,
+    /// Handed to the host by `take_commits`
+    pub applied: bool,
+}
+
+impl Proposal {
+    /// Still waiting to be applied
+    pub fn is_pending(&self) -> bool {
+        !self.applied && matches!(self.state, ProposalState::Hold | ProposalState::Commit)
+    }
+
+    fn is_live(&self) -> bool {
+        matches!(self.state, ProposalState::Hold | ProposalState::Commit)
+    }
+
+    /// Landed in the host text and still undoable by `rollback`
+    pub fn is_applied_commit(&self) -> bool {
+        self.applied && self.state == ProposalState::Commit
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StagingOptions {
+    /// Oldest proposals are evicted beyond this (1..=256); applied commits go last
+    pub max_proposals: usize,
+    /// Unapplied proposals older than this are dropped by `cleanup`
+    pub ttl_ms: u64,
+    /// τ_discard: held proposals below this confidence are discarded
+    pub discard_below: f32,
+}
+
+impl Default for StagingOptions {
+    fn default() -> Self {
+        Self { max_proposals: 64, ttl_ms: 3_000, discard_below: 0.3 }
+    }
+}
+
+pub struct StagingBuffer {
+    options: StagingOptions,
+    items: Vec<Proposal>,
+    next_id: ProposalId,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for StagingBuffer {
+    fn default() -> Self {
+        Self::new(StagingOptions::default())
+    }
+}
+
+impl StagingBuffer {
+    pub fn new(options: StagingOptions) -> Self {
+        Self::with_clock(options, clock::system())
+    }
+
+    pub fn with_clock(mut options: StagingOptions, clock: Arc<dyn Clock>) -> Self {
+        options.max_proposals = options.max_proposals.clamp(1, 256);
+        options.ttl_ms = options.ttl_ms.clamp(1, 600_000);
+        Self { options, items: Vec::new(), next_id: 1, clock }
+    }
+
+    pub fn list(&self) -> &[Proposal] {
+        &self.items
+    }
+
+    pub fn get(&self, id: ProposalId) -> Option<&Proposal> {
+        self.items.iter().find(|p| p.id == id)
+    }
+
+    /// Stage a proposal against `text`; replaces a pending proposal with the same range.
+    /// `None` when the range does not fit `text`.
+    pub fn hold(&mut self, text: &str, proposal: StageEdit) -> Option<ProposalId> {
+        let range = proposal.edit.range;
+        let original = text.get(range.clone())?.to_string();
+        self.items.retain(|p| !(p.is_pending() && p.range == range));
+        if self.items.len() >= self.options.max_proposals {
+            let oldest = self.items.iter().position(|p| !p.is_applied_commit()).unwrap_or(0);
+            self.items.remove(oldest);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push(Proposal {
+            id,
+            stage: proposal.stage,
+            range,
+            replacement: proposal.edit.replacement,
+            original,
+            confidence: proposal.confidence,
+            created_ms: self.clock.now_ms(),
+            state: ProposalState::Hold,
+            applied: false,
+        });
+        Some(id)
+    }
+
+    /// Record a gate decision for a pending proposal (`updateScore`)
+    pub fn decide(&mut self, id: ProposalId, state: ProposalState) -> Option<&Proposal> {
+        let item = self.items.iter_mut().find(|p| p.id == id && p.is_pending())?;
+        item.state = state;
+        Some(item)
+    }
+
+    /// Run held proposals that end before the caret through the confidence gate
+    /// (`score_proposal` against `compute_dynamic_thresholds`, τ_commit starting at
+    /// `config.base_threshold`); returns how many were committed
+    pub fn promote(&mut self, text: &str, caret: usize, config: &ConfidenceConfig) -> usize {
+        let now = self.clock.now_ms();
+        let base = GateThresholds { commit: config.base_threshold, ..GateThresholds::default() };
+        let mut committed = 0;
+        for p in self.items.iter_mut().filter(|p| !p.applied && p.state == ProposalState::Hold) {
+            if p.confidence < self.options.discard_below {
+                p.state = ProposalState::Discard;
+                continue;
+            }
+            if p.range.end > caret {
+                continue;
+            }
+            let edit_type = edit_type(p.stage);
+            let decay = temporal_decay(now.saturating_sub(p.created_ms), self.options.ttl_ms);
+            let score = score_proposal(text, caret, &p.range, p.confidence, decay);
+            let thresholds = compute_dynamic_thresholds(&DynamicThresholdsInput {
+                base,
+                ..DynamicThresholdsInput::new(caret, p.range.clone(), edit_type)
+            });
+            p.state = apply_thresholds(&score, &thresholds, edit_type == EditType::Tone).into();
+            committed += (p.state == ProposalState::Commit) as usize;
+        }
+        committed
+    }
+
+    /// Edits for every committed proposal, relative to the current text (apply with
+    /// `TextEdit::apply_all`). Overlapping commits after the first are discarded.
+    pub fn take_commits(&mut self) -> Vec<TextEdit> {
+        let mut ready: Vec<usize> = (0..self.items.len())
+            .filter(|&i| !self.items[i].applied && self.items[i].state == ProposalState::Commit)
+            .collect();
+        ready.sort_by_key(|&i| (self.items[i].range.start, self.items[i].range.end));
+        let mut cursor = 0;
+        ready.retain(|&i| {
+            let p = &mut self.items[i];
+            if p.range.start < cursor {
+                p.state = ProposalState::Discard;
+                return false;
+            }
+            cursor = p.range.end;
+            true
+        });
+
+        let edits: Vec<TextEdit> = ready
+            .iter()
+            .map(|&i| TextEdit::new(self.items[i].range.clone(), self.items[i].replacement.clone()))
+            .collect();
+        // Back to front, so each shift only touches ranges after the edit
+        for &i in ready.iter().rev() {
+            let old = self.items[i].range.clone();
+            let new_end = old.start + self.items[i].replacement.len();
+            self.items[i].range = old.start..new_end;
+            self.items[i].applied = true;
+            self.shift_after(i, old.end, new_end as isize - old.end as isize);
+        }
+        edits
+    }
+
+    /// A user edit landed: overlapping proposals are invalidated, later ones shift.
+    /// Returns how many were invalidated.
+    pub fn on_user_edit(&mut self, edit: &TextEdit) -> usize {
+        let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+        let mut invalidated = 0;
+        for p in self.items.iter_mut().filter(|p| p.is_live()) {
+            if overlaps(&edit.range, &p.range) {
+                p.state = ProposalState::Discard;
+                invalidated += 1;
+            } else if p.range.start >= edit.range.end {
+                p.range = shift(&p.range, delta);
+            }
+        }
+        invalidated
+    }
+
+    /// Caret entered a pending proposal: withdraw it. Returns how many were withdrawn.
+    pub fn on_caret_move(&mut self, caret: usize) -> usize {
+        let mut rolled = 0;
+        for p in self.items.iter_mut().filter(|p| p.is_pending()) {
+            if p.range.start <= caret && caret <= p.range.end {
+                p.state = ProposalState::Rollback;
+                rolled += 1;
+            }
+        }
+        rolled
+    }
+
+    /// Undo an applied commit; returns the inverse edit for the current text
+    pub fn rollback(&mut self, id: ProposalId) -> Option<TextEdit> {
+        let i = self.items.iter().position(|p| p.id == id && p.is_applied_commit())?;
+        let p = &mut self.items[i];
+        p.state = ProposalState::Rollback;
+        let inverse = TextEdit::new(p.range.clone(), p.original.clone());
+        let delta = p.original.len() as isize - p.range.len() as isize;
+        self.shift_after(i, inverse.range.end, delta);
+        Some(inverse)
+    }
+
+    /// Give up the ability to roll back an applied commit (e.g. once the host's undo
+    /// history no longer reaches it); false for an unknown or unapplied id
+    pub fn release(&mut self, id: ProposalId) -> bool {
+        let before = self.items.len();
+        self.items.retain(|p| !(p.id == id && p.is_applied_commit()));
+        self.items.len() < before
+    }
+
+    /// Drop discarded, rolled-back and expired proposals; applied commits stay
+    /// until they are rolled back or released
+    pub fn cleanup(&mut self) {
+        let now = self.clock.now_ms();
+        let ttl = self.options.ttl_ms;
+        self.items
+            .retain(|p| p.is_applied_commit() || (p.is_live() && now.saturating_sub(p.created_ms) <= ttl));
+    }
+
+    fn shift_after(&mut self, skip: usize, from: usize, delta: isize) {
+        for (j, p) in self.items.iter_mut().enumerate() {
+            if j != skip && p.is_live() && p.range.start >= from {
+                p.range = shift(&p.range, delta);
+            }
+        }
+    }
+}
+
+fn edit_type(stage: Stage) -> EditType {
+    match stage {
+        Stage::Noise => EditType::Noise,
+        Stage::Context => EditType::Context,
+        Stage::Tone => EditType::Tone,
+    }
+}
+
+/// Ranges share text; an insertion overlaps only a range it falls strictly inside
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+fn shift(range: &Range<usize>, delta: isize) -> Range<usize> {
+    let at = |i: usize| i.saturating_add_signed(delta);
+    at(range.start)..at(range.end)
+}
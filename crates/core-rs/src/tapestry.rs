@@ -1,37 +1,299 @@
-/*
-╔══════════════════════════════════════════════════════════════╗
-║  ░  T A P E S T R Y   ( S P A N  T R A C K I N G )      ░░░░  ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-║           ╌╌  P L A C E H O L D E R  ╌╌                      ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-║                                                              ║
-╚══════════════════════════════════════════════════════════════╝
-  • WHAT ▸ Tracks validated, unvalidated, and animated regions
-  • WHY  ▸ Basis for band rendering and undo grouping
-  • HOW  ▸ Minimal span representation with timestamps
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  T A P E S T R Y   ( S P A N  T R A C K I N G )      ░░░░  ║
+  ║                                                              ║
+  ║   Validated spans plus isolated undo units for system edits. ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Tracks validated, unvalidated, and animated regions;
+           groups system edits into undo units (core/undoIsolation.ts)
+  • WHY  ▸ Basis for band rendering and undo grouping; one undo
+           reverts one sweep, never the user's own typing
+  • HOW  ▸ Disjoint kind-tagged spans in an interval map that
+           shift/split on edits; time-bucketed groups of before/after
+           edits whose ranges are rebased over every later edit
 */
 
-#[derive(Clone, Debug)]
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::diff::TextEdit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpanKind {
+    /// Swept and accepted; rendered as settled text
+    Validated,
+    /// Typed but not yet swept
+    Unvalidated,
+    /// Inside a running band/swap animation
+    Animated,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
+    pub kind: SpanKind,
     pub applied_at_ms: u64,
 }
 
-#[derive(Default)]
+impl Span {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// Disjoint spans keyed by start. Because spans never overlap, their ends are
+/// sorted too, so a BTreeMap serves as the interval tree: any range query is
+/// O(log n + k). Touching spans of the same kind are coalesced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpanMap {
+    spans: BTreeMap<usize, Span>,
+}
+
+impl SpanMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// All spans, ascending
+    pub fn iter(&self) -> impl Iterator<Item = &Span> {
+        self.spans.values()
+    }
+
+    /// Give `range` the `kind`, replacing whatever covered it
+    pub fn mark(&mut self, range: Range<usize>, kind: SpanKind, now_ms: u64) {
+        if range.is_empty() {
+            return;
+        }
+        self.carve(&range);
+        self.insert(Span { start: range.start, end: range.end, kind, applied_at_ms: now_ms });
+    }
+
+    /// Remove every span from `range`, trimming or splitting the ones it cuts
+    pub fn clear(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.carve(&range);
+        }
+    }
+
+    /// Kind of the span covering byte `pos`
+    pub fn kind_at(&self, pos: usize) -> Option<SpanKind> {
+        self.spans.range(..=pos).next_back().filter(|(_, s)| s.end > pos).map(|(_, s)| s.kind)
+    }
+
+    /// Spans intersecting `range`, clipped to it
+    pub fn spans_in(&self, range: Range<usize>) -> Vec<Span> {
+        let mut out: Vec<Span> = self
+            .spans
+            .range(..range.end)
+            .rev()
+            .take_while(|(_, s)| s.end > range.start)
+            .map(|(_, s)| Span { start: s.start.max(range.start), end: s.end.min(range.end), ..s.clone() })
+            .collect();
+        out.reverse();
+        out
+    }
+
+    /// Parts of `range` with the given kind, e.g. "what is validated in [a, b)"
+    pub fn query(&self, range: Range<usize>, kind: SpanKind) -> Vec<Range<usize>> {
+        self.spans_in(range).into_iter().filter(|s| s.kind == kind).map(|s| s.range()).collect()
+    }
+
+    /// Move spans over an edit of the text: spans after it shift, spans it cuts
+    /// lose the replaced part, and an insertion inside a span splits it (the
+    /// inserted text belongs to no span)
+    pub fn apply_edit(&mut self, edit: &TextEdit) {
+        let Range { start, end } = edit.range;
+        let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+        let from = match self.spans.range(..start).next_back() {
+            Some((&key, s)) if s.end > start => key,
+            _ => start,
+        };
+        let tail = self.spans.split_off(&from);
+        for span in tail.into_values() {
+            if span.start < start {
+                self.insert(Span { end: span.end.min(start), ..span.clone() });
+            }
+            if span.end > end {
+                let shift = |i: usize| i.saturating_add_signed(delta);
+                self.insert(Span { start: shift(span.start.max(end)), end: shift(span.end), ..span });
+            }
+        }
+    }
+
+    fn carve(&mut self, range: &Range<usize>) {
+        let cut: Vec<usize> = self
+            .spans
+            .range(..range.end)
+            .rev()
+            .take_while(|(_, s)| s.end > range.start)
+            .map(|(&k, _)| k)
+            .collect();
+        for key in cut {
+            let Some(span) = self.spans.remove(&key) else { continue };
+            if span.start < range.start {
+                self.spans.insert(span.start, Span { end: range.start, ..span.clone() });
+            }
+            if span.end > range.end {
+                self.spans.insert(range.end, Span { start: range.end, ..span });
+            }
+        }
+    }
+
+    /// Insert into a gap, merging with touching neighbours of the same kind
+    fn insert(&mut self, mut span: Span) {
+        if span.start >= span.end {
+            return;
+        }
+        let touching = |s: &Span| s.kind == span.kind;
+        let prev = self.spans.range(..span.start).next_back().filter(|(_, p)| p.end == span.start && touching(p)).map(|(&k, _)| k);
+        if let Some(prev) = prev.and_then(|k| self.spans.remove(&k)) {
+            span.start = prev.start;
+            span.applied_at_ms = span.applied_at_ms.max(prev.applied_at_ms);
+        }
+        let next = self.spans.get(&span.end).filter(|n| touching(n)).map(|n| n.start);
+        if let Some(next) = next.and_then(|k| self.spans.remove(&k)) {
+            span.end = next.end;
+            span.applied_at_ms = span.applied_at_ms.max(next.applied_at_ms);
+        }
+        self.spans.insert(span.start, span);
+    }
+}
+
+/// A system edit as applied, kept so it can be reverted
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemEdit {
+    /// Where `after` sits in the current text (rebased over later edits)
+    pub range: Range<usize>,
+    /// Text the edit replaced
+    pub before: String,
+    /// Text the edit inserted
+    pub after: String,
+    pub applied_at_ms: u64,
+    /// False once a later edit rewrote part of `after`; such edits are not reverted
+    pub revertible: bool,
+}
+
+/// System edits applied within one time bucket, undone together
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoGroup {
+    pub id: u64,
+    pub edits: Vec<SystemEdit>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
 pub struct Tapestry {
-    pub validated: Vec<Span>,
+    /// Validated / unvalidated / animated regions of the text
+    pub spans: SpanMap,
+    /// Edits closer together than this share a group (50..=500 ms)
+    pub bucket_ms: u64,
+    groups: Vec<UndoGroup>,
+    /// The last group still accepts edits (no user edit since)
+    open: bool,
+    next_id: u64,
 }
 
-impl Tapestry {
-    pub fn new() -> Self { Self { validated: Vec::new() } }
-    pub fn push(&mut self, span: Span) { self.validated.push(span); }
+impl Default for Tapestry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+impl Tapestry {
+    pub fn new() -> Self { Self::with_bucket_ms(150) }
+
+    pub fn with_bucket_ms(bucket_ms: u64) -> Self {
+        Self { spans: SpanMap::new(), bucket_ms: bucket_ms.clamp(50, 500), groups: Vec::new(), open: false, next_id: 1 }
+    }
+
+    pub fn push(&mut self, span: Span) { self.spans.mark(span.range(), span.kind, span.applied_at_ms); }
+
+    pub fn groups(&self) -> &[UndoGroup] {
+        &self.groups
+    }
 
+    /// Record a system edit about to be applied to `text`; returns its group id,
+    /// or `None` if the range does not fit `text`
+    pub fn record_system_edit(&mut self, text: &str, edit: &TextEdit, now_ms: u64) -> Option<u64> {
+        let before = text.get(edit.range.clone())?.to_string();
+        self.rebase(edit);
+        let joins = self.open && self.groups.last().is_some_and(|g| now_ms.saturating_sub(g.end_ms) <= self.bucket_ms);
+        if !joins {
+            self.groups.push(UndoGroup { id: self.next_id, edits: Vec::new(), start_ms: now_ms, end_ms: now_ms });
+            self.next_id += 1;
+        }
+        self.open = true;
+        let group = self.groups.last_mut()?;
+        group.end_ms = now_ms;
+        group.edits.push(SystemEdit {
+            range: edit.range.start..edit.range.start + edit.replacement.len(),
+            before,
+            after: edit.replacement.clone(),
+            applied_at_ms: now_ms,
+            revertible: true,
+        });
+        Some(group.id)
+    }
 
+    /// Record a user edit (relative to the current text). It never joins a group,
+    /// closes the open one, and moves recorded system edits along.
+    pub fn record_user_edit(&mut self, edit: &TextEdit) {
+        self.rebase(edit);
+        self.open = false;
+    }
+
+    /// Inverses of the last group's edits against the current text, right to left
+    /// (apply in order, or all at once with `TextEdit::apply_all`)
+    pub fn peek_last_group(&self) -> Vec<TextEdit> {
+        let Some(group) = self.groups.last() else { return Vec::new() };
+        let mut inverse: Vec<TextEdit> = group
+            .edits
+            .iter()
+            .filter(|e| e.revertible)
+            .map(|e| TextEdit::new(e.range.clone(), e.before.clone()))
+            .collect();
+        inverse.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+        inverse
+    }
+
+    /// Pop the last group and return its inverse edits (see `peek_last_group`)
+    pub fn undo_last_group(&mut self) -> Vec<TextEdit> {
+        let inverse = self.peek_last_group();
+        let Some(group) = self.groups.pop() else { return inverse };
+        let skipped = group.edits.iter().filter(|e| !e.revertible).count();
+        if skipped > 0 {
+            log::debug!("undo group {}: {} edit(s) rewritten since, left in place", group.id, skipped);
+        }
+        // Right to left, so each inverse only moves edits after it
+        for edit in &inverse {
+            self.rebase(edit);
+        }
+        self.open = false;
+        inverse
+    }
+
+    /// Shift spans and recorded edits past `edit`; recorded edits it overlaps
+    /// stop being revertible
+    fn rebase(&mut self, edit: &TextEdit) {
+        self.spans.apply_edit(edit);
+        let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+        let shift = |i: usize| i.saturating_add_signed(delta);
+        for recorded in self.groups.iter_mut().flat_map(|g| g.edits.iter_mut()) {
+            let r = &recorded.range;
+            if edit.range.start < r.end && r.start < edit.range.end {
+                recorded.revertible = false;
+            } else if r.start >= edit.range.end {
+                recorded.range = shift(r.start)..shift(r.end);
+            }
+        }
+    }
+}
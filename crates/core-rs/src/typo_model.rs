@@ -0,0 +1,235 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  T Y P O   M O D E L  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   How likely a typed word is, given the word that was meant. ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Keyboard layouts (QWERTY, QWERTZ, AZERTY, Dvorak) and a
+           noisy-channel model of substitutions, insertions,
+           deletions and transpositions
+  • WHY  ▸ Rank candidate corrections by physical plausibility
+           instead of hard-coded lists ("wprld" → "world")
+  • HOW  ▸ Keys get staggered grid positions; neighbouring keys make
+           fat-finger errors likely; the best alignment (optimal
+           string alignment DP over -ln p) scores a pair
+*/
+
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Qwertz,
+    Azerty,
+    Dvorak,
+}
+
+impl KeyboardLayout {
+    /// Parse "QWERTY" / "QWERTZ" / "AZERTY" / "Dvorak" (any case)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "qwerty" => Some(KeyboardLayout::Qwerty),
+            "qwertz" => Some(KeyboardLayout::Qwertz),
+            "azerty" => Some(KeyboardLayout::Azerty),
+            "dvorak" => Some(KeyboardLayout::Dvorak),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => "QWERTY",
+            KeyboardLayout::Qwertz => "QWERTZ",
+            KeyboardLayout::Azerty => "AZERTY",
+            KeyboardLayout::Dvorak => "Dvorak",
+        }
+    }
+
+    /// Unshifted keys, number row first
+    fn rows(&self) -> [&'static str; 4] {
+        match self {
+            KeyboardLayout::Qwerty => ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"],
+            KeyboardLayout::Qwertz => ["1234567890ß", "qwertzuiopü+", "asdfghjklöä#", "yxcvbnm,.-"],
+            KeyboardLayout::Azerty => ["&é\"'(-è_çà)=", "azertyuiop^$", "qsdfghjklmù*", "wxcvbn,;:!"],
+            KeyboardLayout::Dvorak => ["1234567890[]", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"],
+        }
+    }
+
+    /// Key centre in key widths, with the usual row stagger
+    pub fn position(&self, c: char) -> Option<(f32, f32)> {
+        const STAGGER: [f32; 4] = [0.0, 0.5, 0.75, 1.25];
+        let c = c.to_lowercase().next()?;
+        self.rows().iter().enumerate().find_map(|(row, keys)| {
+            keys.chars().position(|k| k == c).map(|col| (row as f32, col as f32 + STAGGER[row]))
+        })
+    }
+
+    /// Distance between two keys in key widths; `None` when either is not on the layout
+    pub fn distance(&self, a: char, b: char) -> Option<f32> {
+        let ((ra, ca), (rb, cb)) = (self.position(a)?, self.position(b)?);
+        Some(((ra - rb).powi(2) + (ca - cb).powi(2)).sqrt())
+    }
+
+    /// Neighbouring keys (same row or diagonally above/below)
+    pub fn is_adjacent(&self, a: char, b: char) -> bool {
+        self.distance(a, b).is_some_and(|d| d > 0.0 && d <= 1.25)
+    }
+}
+
+lazy_static! {
+    /// Layout hosts last reported, and how many reports there have been
+    static ref GLOBAL_LAYOUT: Mutex<(KeyboardLayout, u64)> = Mutex::new((KeyboardLayout::default(), 0));
+}
+
+/// Process-wide layout (set by hosts through `mind_type_set_keyboard_layout`)
+pub fn global_layout() -> KeyboardLayout {
+    global_layout_report().1
+}
+
+/// Report a layout change; running engines and schedulers adopt it on their next tick
+pub fn set_global_layout(layout: KeyboardLayout) {
+    if let Ok(mut current) = GLOBAL_LAYOUT.lock() {
+        *current = (layout, current.1 + 1);
+    }
+}
+
+/// (report count, layout): a count that moved since the last look means the host switched layouts
+pub fn global_layout_report() -> (u64, KeyboardLayout) {
+    GLOBAL_LAYOUT.lock().map(|g| (g.1, g.0)).unwrap_or_default()
+}
+
+/// Per-character error probabilities
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TypoParams {
+    /// Neighbouring key hit instead of the intended one
+    pub adjacent_substitution: f32,
+    /// Any other key hit instead
+    pub substitution: f32,
+    /// Extra key next to (or equal to) the intended neighbour
+    pub adjacent_insertion: f32,
+    pub insertion: f32,
+    pub deletion: f32,
+    /// Second of a doubled letter dropped ("leter")
+    pub double_deletion: f32,
+    /// Two neighbouring characters swapped ("teh")
+    pub transposition: f32,
+}
+
+impl Default for TypoParams {
+    fn default() -> Self {
+        Self {
+            adjacent_substitution: 0.01,
+            substitution: 0.000_5,
+            adjacent_insertion: 0.005,
+            insertion: 0.000_5,
+            deletion: 0.002,
+            double_deletion: 0.006,
+            transposition: 0.02,
+        }
+    }
+}
+
+/// A candidate with its score, best first from `rank`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedCandidate<'a> {
+    pub word: &'a str,
+    /// ln P(typed | word) + ln prior
+    pub score: f32,
+    /// Share of the probability mass among the ranked candidates
+    pub posterior: f32,
+}
+
+/// Noisy-channel typo model for one layout
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TypoModel {
+    pub layout: KeyboardLayout,
+    pub params: TypoParams,
+}
+
+impl TypoModel {
+    pub fn new(layout: KeyboardLayout) -> Self {
+        Self { layout, params: TypoParams::default() }
+    }
+
+    /// P(typed `typed` | intended `intended`) for one substituted key
+    pub fn substitution(&self, intended: char, typed: char) -> f32 {
+        if self.layout.is_adjacent(intended, typed) {
+            self.params.adjacent_substitution
+        } else {
+            self.params.substitution
+        }
+    }
+
+    /// P(extra `typed` next to intended `neighbour`)
+    pub fn insertion(&self, neighbour: Option<char>, typed: char) -> f32 {
+        match neighbour {
+            Some(n) if n == typed || self.layout.is_adjacent(n, typed) => self.params.adjacent_insertion,
+            _ => self.params.insertion,
+        }
+    }
+
+    /// P(intended `c` dropped, after intended `previous`)
+    pub fn deletion(&self, previous: Option<char>, c: char) -> f32 {
+        if previous == Some(c) {
+            self.params.double_deletion
+        } else {
+            self.params.deletion
+        }
+    }
+
+    /// ln P(typed | intended) along the most likely alignment (case-insensitive);
+    /// 0.0 for identical words
+    pub fn log_likelihood(&self, typed: &str, intended: &str) -> f32 {
+        let t: Vec<char> = typed.chars().flat_map(char::to_lowercase).collect();
+        let w: Vec<char> = intended.chars().flat_map(char::to_lowercase).collect();
+        let cost = |p: f32| -p.ln();
+        // d[i][j]: cost of typing t[..j] when w[..i] was meant
+        let mut d = vec![vec![0.0f32; t.len() + 1]; w.len() + 1];
+        for j in 1..=t.len() {
+            d[0][j] = d[0][j - 1] + cost(self.insertion(w.first().copied(), t[j - 1]));
+        }
+        for i in 1..=w.len() {
+            d[i][0] = d[i - 1][0] + cost(self.deletion(i.checked_sub(2).map(|k| w[k]), w[i - 1]));
+            for j in 1..=t.len() {
+                let matched = if w[i - 1] == t[j - 1] { 0.0 } else { cost(self.substitution(w[i - 1], t[j - 1])) };
+                let mut best = (d[i - 1][j - 1] + matched)
+                    .min(d[i][j - 1] + cost(self.insertion(Some(w[i - 1]), t[j - 1])))
+                    .min(d[i - 1][j] + cost(self.deletion(i.checked_sub(2).map(|k| w[k]), w[i - 1])));
+                if i > 1 && j > 1 && w[i - 1] == t[j - 2] && w[i - 2] == t[j - 1] && w[i - 1] != w[i - 2] {
+                    best = best.min(d[i - 2][j - 2] + cost(self.params.transposition));
+                }
+                d[i][j] = best;
+            }
+        }
+        -d[w.len()][t.len()]
+    }
+
+    /// Candidates ordered by how plausibly they produced `typed` (equal priors)
+    pub fn rank<'a>(&self, typed: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<RankedCandidate<'a>> {
+        self.rank_with_priors(typed, candidates.into_iter().map(|w| (w, 1.0)))
+    }
+
+    /// As `rank`, weighting each candidate by a prior (e.g. word frequency)
+    pub fn rank_with_priors<'a>(
+        &self,
+        typed: &str,
+        candidates: impl IntoIterator<Item = (&'a str, f32)>,
+    ) -> Vec<RankedCandidate<'a>> {
+        let mut ranked: Vec<RankedCandidate> = candidates
+            .into_iter()
+            .filter(|(_, prior)| *prior > 0.0)
+            .map(|(word, prior)| RankedCandidate { word, score: self.log_likelihood(typed, word) + prior.ln(), posterior: 0.0 })
+            .collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.word.cmp(b.word)));
+        let best = ranked.first().map_or(0.0, |c| c.score);
+        let norm: f32 = ranked.iter().map(|c| (c.score - best).exp()).sum();
+        for c in ranked.iter_mut() {
+            c.posterior = (c.score - best).exp() / norm;
+        }
+        ranked
+    }
+}
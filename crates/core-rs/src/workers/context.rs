@@ -1,13 +1,270 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   S T U B  ░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Stub for workers/$f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real implementation
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  C O N T E X T   W O R K E R  ░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Sentence-level repairs with look-behind context.           ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Context stage (port of engines/contextTransformer.ts)
+  • WHY  ▸ REQ-CONTEXT-TRANSFORMER; runs on short pauses
+  • HOW  ▸ Window = sentence at caret + N previous; deterministic
+           repairs first, optional LM pass; edits end before caret
 */
 
-#[allow(dead_code)]
-pub fn _stub_worker_context() {}
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::diff::{CaretSafeDiff, TextEdit};
+use crate::engine::Corrector;
+use crate::fragment::{self, FragmentExtractor, Sentence};
+use crate::language_detection::Language;
+use crate::offsets::floor_char_boundary;
+
+/// Sentences around the caret as byte ranges of the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextWindow {
+    /// Sentence containing the caret; empty at the caret when it sits between sentences
+    pub current: Range<usize>,
+    /// Up to N previous sentences, nearest first (S-1, S-2, …)
+    pub previous: Vec<Range<usize>>,
+    /// Up to two sentences after the current one; read-only lookahead
+    pub next: Vec<Range<usize>>,
+}
+
+impl ContextWindow {
+    /// From the oldest previous sentence up to the caret
+    pub fn editable(&self, caret: usize) -> Range<usize> {
+        let start = self.previous.last().unwrap_or(&self.current).start;
+        start..self.current.end.min(caret).max(start)
+    }
+}
+
+/// Sentence at the caret plus `previous` sentences before it (like `buildContextWindow`)
+pub fn build_context_window(text: &str, caret: usize, previous: usize) -> ContextWindow {
+    let sentences = FragmentExtractor::new().segment(text);
+    build_from_sentences(&sentences, caret, previous)
+}
+
+fn build_from_sentences(sentences: &[Sentence], caret: usize, previous: usize) -> ContextWindow {
+    let at = sentences.iter().position(|s| s.range.start <= caret && caret <= s.range.end);
+    let (current, before) = match at {
+        Some(i) => (sentences[i].range.clone(), i),
+        None => (caret..caret, sentences.iter().take_while(|s| s.range.end <= caret).count()),
+    };
+    let after = at.map_or(before, |i| i + 1);
+    ContextWindow {
+        current,
+        previous: sentences[..before].iter().rev().take(previous).map(|s| s.range.clone()).collect(),
+        next: sentences[after..].iter().take(2).map(|s| s.range.clone()).collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairKind {
+    /// Sentence-initial capital, or English standalone "i"
+    Capitalization,
+    /// "the the" → "the"
+    DuplicateWord,
+    /// Period added to a sentence ended only by a line break
+    TerminalPunctuation,
+    /// Delegated to the LM backend
+    Lm,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextProposal {
+    pub kind: RepairKind,
+    /// Byte range of the document; always ends before the caret
+    pub edit: TextEdit,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextResult {
+    pub window: ContextWindow,
+    /// Sorted by range start
+    pub proposals: Vec<ContextProposal>,
+}
+
+pub struct ContextConfig {
+    /// Previous sentences in the window (TS uses 2: S-1 weight 1.0, S-2 weight 0.5)
+    pub previous_sentences: usize,
+    /// Doubled words that are usually intentional
+    pub allowed_duplicates: Vec<String>,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            previous_sentences: 2,
+            allowed_duplicates: vec!["had".into(), "that".into()],
+        }
+    }
+}
+
+pub struct ContextWorker {
+    pub config: ContextConfig,
+    lm: Option<Box<dyn Corrector>>,
+}
+
+impl Default for ContextWorker {
+    fn default() -> Self {
+        Self::new(ContextConfig::default())
+    }
+}
+
+impl ContextWorker {
+    pub fn new(config: ContextConfig) -> Self {
+        Self { config, lm: None }
+    }
+
+    /// Also run the current sentence through an LM (or any `Corrector`)
+    pub fn with_lm(mut self, lm: Box<dyn Corrector>) -> Self {
+        self.lm = Some(lm);
+        self
+    }
+
+    pub fn set_lm(&mut self, lm: Option<Box<dyn Corrector>>) {
+        self.lm = lm;
+    }
+
+    pub fn window(&self, text: &str, caret: usize) -> ContextWindow {
+        build_context_window(text, caret, self.config.previous_sentences)
+    }
+
+    /// Proposals for the window around byte `caret`
+    pub fn propose(&mut self, text: &str, caret: usize, language: &Language) -> ContextResult {
+        let caret = floor_char_boundary(text, caret);
+        let sentences = FragmentExtractor::for_language(language.clone()).segment(text);
+        let window = build_from_sentences(&sentences, caret, self.config.previous_sentences);
+
+        let mut proposals = Vec::new();
+        let mut spans: Vec<&Sentence> = sentences
+            .iter()
+            .filter(|s| window.previous.contains(&s.range) || s.range == window.current)
+            .collect();
+        spans.sort_by_key(|s| s.range.start);
+        for sentence in spans {
+            let end = sentence.range.end.min(caret);
+            let words = complete_words(text, sentence.range.start..end, caret);
+            self.capitalization(text, &words, language, &mut proposals);
+            self.duplicates(text, &words, &mut proposals);
+            if sentence.terminated && sentence.range.end < caret {
+                terminal_punctuation(text, sentence, &words, &mut proposals);
+            }
+        }
+        if let Some(lm) = self.lm.as_mut() {
+            lm_proposals(lm.as_mut(), text, &window, caret, language, &mut proposals);
+        }
+        proposals.retain(|p| p.edit.range.end < caret && p.edit.range.start < caret);
+        proposals.sort_by_key(|p| (p.edit.range.start, p.edit.range.end));
+        ContextResult { window, proposals }
+    }
+
+    fn capitalization(&self, text: &str, words: &[Range<usize>], language: &Language, out: &mut Vec<ContextProposal>) {
+        // Sentence start, plus words after ". " the segmenter kept in one
+        // sentence because they were lowercase ("hello there. so")
+        let starts = words.iter().enumerate().filter(|&(i, _)| {
+            i == 0 || {
+                let gap = &text[words[i - 1].end..words[i].start];
+                let previous = &text[words[i - 1].clone()];
+                matches!(gap.trim_end(), "." | "!" | "?")
+                    && gap.ends_with(char::is_whitespace)
+                    && !(gap.starts_with('.') && (previous.chars().count() == 1 || fragment::is_abbreviation(language, previous)))
+            }
+        });
+        for (_, word) in starts {
+            let mut chars = text[word.clone()].chars();
+            let head = chars.next().filter(|c| c.is_lowercase());
+            // Leave "iPhone"-style words alone
+            if let Some(head) = head.filter(|_| !chars.any(char::is_uppercase)) {
+                let upper: String = head.to_uppercase().collect();
+                let range = word.start..word.start + head.len_utf8();
+                out.push(proposal(RepairKind::Capitalization, range, &upper, 0.95));
+            }
+        }
+        if *language == Language::English {
+            let singles: Vec<Range<usize>> = words
+                .iter()
+                .filter(|w| &text[(*w).clone()] == "i" && !out.iter().any(|p| p.edit.range.start == w.start))
+                .cloned()
+                .collect();
+            for word in singles {
+                out.push(proposal(RepairKind::Capitalization, word, "I", 0.9));
+            }
+        }
+    }
+
+    fn duplicates(&self, text: &str, words: &[Range<usize>], out: &mut Vec<ContextProposal>) {
+        for pair in words.windows(2) {
+            let (a, b) = (&text[pair[0].clone()], &text[pair[1].clone()]);
+            let gap = &text[pair[0].end..pair[1].start];
+            let repeated = a.to_lowercase() == b.to_lowercase() && a.chars().any(char::is_alphabetic);
+            let allowed = self.config.allowed_duplicates.iter().any(|d| d.eq_ignore_ascii_case(a));
+            if repeated && !allowed && !gap.is_empty() && gap.chars().all(char::is_whitespace) {
+                out.push(proposal(RepairKind::DuplicateWord, pair[0].end..pair[1].end, "", 0.9));
+            }
+        }
+    }
+}
+
+/// "Meeting notes from today\n" → add a period; single-word lines (titles) are skipped
+fn terminal_punctuation(text: &str, sentence: &Sentence, words: &[Range<usize>], out: &mut Vec<ContextProposal>) {
+    let body = &text[sentence.range.clone()];
+    let last = body.chars().next_back();
+    let ends_in_word = last.is_some_and(|c| c.is_alphanumeric() || matches!(c, '"' | '”' | '’' | ')' | ']'));
+    let has_terminator = body.trim_end_matches(['"', '”', '’', ')', ']']).ends_with(['.', '!', '?', '…', '。', '！', '？', ':', ';']);
+    if ends_in_word && !has_terminator && words.len() >= 3 {
+        let at = sentence.range.end;
+        out.push(proposal(RepairKind::TerminalPunctuation, at..at, ".", 0.75));
+    }
+}
+
+fn lm_proposals(
+    lm: &mut dyn Corrector,
+    text: &str,
+    window: &ContextWindow,
+    caret: usize,
+    language: &Language,
+    out: &mut Vec<ContextProposal>,
+) {
+    // The sentence being typed, or the last complete one when the caret is between sentences
+    let span = if window.current.start < caret {
+        window.current.start..window.current.end.min(caret)
+    } else {
+        match window.previous.first() {
+            Some(prev) => prev.clone(),
+            None => return,
+        }
+    };
+    let original = &text[span.clone()];
+    let Some(correction) = lm.correct(original, language) else { return };
+    if correction.text == original {
+        return;
+    }
+    let safe = CaretSafeDiff::new(caret - span.start).calculate(original, &correction.text);
+    for edit in safe.edits() {
+        let range = edit.range.start + span.start..edit.range.end + span.start;
+        out.push(ContextProposal {
+            kind: RepairKind::Lm,
+            edit: TextEdit::new(range, edit.replacement),
+            confidence: correction.confidence,
+        });
+    }
+}
+
+/// Word ranges in `range`, excluding a word that runs into the caret
+fn complete_words(text: &str, range: Range<usize>, caret: usize) -> Vec<Range<usize>> {
+    let mut words: Vec<Range<usize>> = text[range.clone()]
+        .unicode_word_indices()
+        .map(|(i, w)| range.start + i..range.start + i + w.len())
+        .collect();
+    if words.last().is_some_and(|w| w.end >= caret) {
+        words.pop();
+    }
+    words
+}
+
+fn proposal(kind: RepairKind, range: Range<usize>, replacement: &str, confidence: f32) -> ContextProposal {
+    ContextProposal { kind, edit: TextEdit::new(range, replacement), confidence }
+}
@@ -12,8 +12,10 @@
   ╚══════════════════════════════════════════════════════════════╝
   • WHAT ▸ Worker thread coordination module
   • WHY  ▸ Manage background processing tasks
-  • HOW  ▸ Context and tone transformation workers
+  • HOW  ▸ Noise, context and tone transformation workers
 */
 
+pub mod noise;
+pub mod rule_packs;
 pub mod context;
 pub mod tone;
@@ -0,0 +1,454 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  N O I S E   W O R K E R  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Forward cleanup (<80 bytes) with caret-safe edits.         ║
+  ║   Works on the live typing zone just behind the caret.       ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Rule-based noise stage (port of engines/noiseTransformer.ts)
+  • WHY  ▸ Cheap, deterministic fixes while the user is still typing
+  • HOW  ▸ Rules run in priority order; first caret-safe match wins.
+           Built-in rules are language-neutral (spacing, punctuation);
+           word fixes, English ones included, come from the input
+           language's rule pack
+*/
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use log::debug;
+
+use crate::default_thresholds::MAX_SWEEP_WINDOW;
+use crate::diff::TextEdit;
+use crate::engine::{Correction, Corrector};
+use crate::language_detection::Language;
+use crate::lexicon::{self, Lexicon, Lexicons};
+use crate::offsets::{ceil_char_boundary, floor_char_boundary};
+use crate::typo_model::{KeyboardLayout, TypoModel};
+use crate::workers::rule_packs::RulePacks;
+
+/// Rules report a confidence; anything below this is dropped
+pub const MIN_CONFIDENCE: f32 = 0.8;
+
+/// Text and caret (byte offsets) a noise pass runs against
+#[derive(Debug, Clone)]
+pub struct NoiseInput<'a> {
+    pub text: &'a str,
+    pub caret: usize,
+    /// Optional range the proposed edit must stay inside
+    pub hint: Option<Range<usize>>,
+    /// Selects the rule pack; `Unknown` runs the built-in rules only
+    pub language: Language,
+}
+
+impl<'a> NoiseInput<'a> {
+    pub fn new(text: &'a str, caret: usize) -> Self {
+        Self { text, caret, hint: None, language: Language::Unknown }
+    }
+
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// The sweep window behind the caret, narrowed by the hint
+    pub fn search_range(&self) -> Option<Range<usize>> {
+        let caret = floor_char_boundary(self.text, self.caret);
+        let mut start = floor_char_boundary(self.text, caret.saturating_sub(MAX_SWEEP_WINDOW));
+        let mut end = caret;
+        if let Some(hint) = &self.hint {
+            start = start.max(ceil_char_boundary(self.text, hint.start));
+            end = end.min(floor_char_boundary(self.text, hint.end));
+        }
+        (start < end).then_some(start..end)
+    }
+}
+
+/// One rule's proposed edit
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseProposal {
+    pub rule: &'static str,
+    pub edit: TextEdit,
+    pub confidence: f32,
+}
+
+pub trait NoiseRule: Send {
+    fn name(&self) -> &'static str;
+    /// Lower runs first
+    fn priority(&self) -> i32;
+    /// Rightmost fix inside `input.search_range()`, if any
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal>;
+    /// Rules scoring keyboard typos switch layout; others ignore it
+    fn set_keyboard_layout(&mut self, _layout: KeyboardLayout) {}
+}
+
+struct RuleEntry {
+    rule: Box<dyn NoiseRule>,
+    enabled: bool,
+}
+
+/// Ordered set of noise rules with per-rule enable flags
+pub struct NoiseRegistry {
+    rules: Vec<RuleEntry>,
+    /// Per-language rules, run alongside the registered ones
+    pub packs: RulePacks,
+}
+
+impl Default for NoiseRegistry {
+    fn default() -> Self {
+        let mut registry = Self { packs: RulePacks::builtin(), ..Self::empty() };
+        registry.register(Box::new(WhitespaceRule));
+        registry.register(Box::new(PunctuationRule));
+        registry
+    }
+}
+
+impl NoiseRegistry {
+    /// Registry with the built-in rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// No rules and no packs
+    pub fn empty() -> Self {
+        Self { rules: Vec::new(), packs: RulePacks::empty() }
+    }
+
+    /// Add a rule; equal priorities keep registration order
+    pub fn register(&mut self, rule: Box<dyn NoiseRule>) {
+        let at = self.rules.partition_point(|e| e.rule.priority() <= rule.priority());
+        self.rules.insert(at, RuleEntry { rule, enabled: true });
+    }
+
+    /// Returns false when no rule has that name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for entry in self.rules.iter_mut().filter(|e| e.rule.name() == name) {
+            entry.enabled = enabled;
+            found = true;
+        }
+        found
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.rules.iter().any(|e| e.enabled && e.rule.name() == name)
+    }
+
+    /// Pass the layout to every rule that scores keyboard typos
+    pub fn set_keyboard_layout(&mut self, layout: KeyboardLayout) {
+        for entry in self.rules.iter_mut() {
+            entry.rule.set_keyboard_layout(layout);
+        }
+    }
+
+    /// Rule names in the order they run
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|e| e.rule.name()).collect()
+    }
+
+    /// First caret-safe proposal from the enabled rules and the input language's
+    /// pack, in priority order (pack rules after registered ones of equal priority)
+    pub fn transform(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        if input.caret == 0 || input.text.is_empty() {
+            return None;
+        }
+        let window = input.search_range()?;
+        let mut rules: Vec<&dyn NoiseRule> = self.rules.iter().filter(|e| e.enabled).map(|e| e.rule.as_ref()).collect();
+        rules.extend(self.packs.rules_for(&input.language).iter().map(|r| r.as_ref()));
+        rules.sort_by_key(|r| r.priority());
+        for rule in rules {
+            let Some(proposal) = rule.apply(input) else { continue };
+            let range = &proposal.edit.range;
+            let safe = range.start >= window.start
+                && range.end <= window.end
+                && !(range.is_empty() && range.start >= input.caret)
+                && proposal.confidence >= MIN_CONFIDENCE
+                && input.text.is_char_boundary(range.start)
+                && input.text.is_char_boundary(range.end);
+            if safe {
+                debug!("[Noise] {} proposes {:?}", proposal.rule, proposal.edit);
+                return Some(proposal);
+            }
+            debug!("[Noise] {} proposal {:?} skipped (outside window)", proposal.rule, proposal.edit);
+        }
+        None
+    }
+
+    /// Apply proposals repeatedly until no rule fires; returns the edits
+    /// in application order with the final text
+    pub fn sweep(&self, text: &str, caret: usize) -> (String, Vec<TextEdit>) {
+        self.sweep_for(text, caret, &Language::Unknown)
+    }
+
+    /// `sweep` with `language`'s rule pack
+    pub fn sweep_for(&self, text: &str, caret: usize, language: &Language) -> (String, Vec<TextEdit>) {
+        const MAX_PASSES: usize = 32;
+        let mut text = text.to_string();
+        let mut caret = caret.min(text.len());
+        let mut applied = Vec::new();
+        for _ in 0..MAX_PASSES {
+            let Some(proposal) = self.transform(&NoiseInput::new(&text, caret).with_language(language.clone())) else { break };
+            let edit = proposal.edit;
+            text.replace_range(edit.range.clone(), &edit.replacement);
+            caret = caret - edit.range.len() + edit.replacement.len();
+            applied.push(edit);
+        }
+        (text, applied)
+    }
+}
+
+impl Corrector for NoiseRegistry {
+    fn correct(&mut self, region: &str, language: &Language) -> Option<Correction> {
+        let (text, applied) = self.sweep_for(region, region.len(), language);
+        (!applied.is_empty()).then_some(Correction { text, confidence: 1.0 })
+    }
+
+    fn set_keyboard_layout(&mut self, layout: KeyboardLayout) {
+        NoiseRegistry::set_keyboard_layout(self, layout);
+    }
+}
+
+/// Collapse runs of spaces/tabs between tokens; drop trailing spaces before a newline
+pub struct WhitespaceRule;
+
+impl NoiseRule for WhitespaceRule {
+    fn name(&self) -> &'static str {
+        "whitespace-normalization"
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        let search = &input.text[window.clone()];
+        let bytes = search.as_bytes();
+        let mut best: Option<(Range<usize>, &str)> = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            if !matches!(bytes[i], b' ' | b'\t') {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < bytes.len() && matches!(bytes[i], b' ' | b'\t') {
+                i += 1;
+            }
+            let run = &search[run_start..i];
+            let before = search[..run_start].chars().next_back();
+            let after = search[i..].chars().next();
+            let candidate = match (before, after) {
+                (_, Some('\n')) => Some(""),
+                (Some(b), Some(a)) if !b.is_whitespace() && !a.is_whitespace() && (run.len() > 1 || run.contains('\t')) => {
+                    Some(" ")
+                }
+                _ => None,
+            };
+            if let Some(replacement) = candidate {
+                best = Some((window.start + run_start..window.start + i, replacement));
+            }
+        }
+        let (range, replacement) = best?;
+        Some(proposal(self.name(), range, replacement, 1.0))
+    }
+}
+
+/// Words missing from a lexicon, replaced by the candidate the keyboard typo
+/// model and word frequencies find most plausible ("wprld" → "world"). Only
+/// lowercase words one likely slip away from a clear winner are touched, using
+/// the input language's lexicon (or an `Unknown` one serving every language).
+/// Contractions are looked up whole, so "don't" is one word, not "don" + "t".
+///
+/// Not registered by default: every valid word missing from the lexicon is a
+/// candidate for rewriting, so hosts opt in once their lexicon is large enough.
+pub struct KeyboardTypoRule {
+    pub model: TypoModel,
+    pub lexicons: Lexicons,
+    /// Candidates less likely than this (ln P(typed | candidate)) are ignored
+    pub min_log_likelihood: f32,
+    /// Score lead (ln) the best candidate needs over the runner-up
+    pub min_margin: f32,
+}
+
+impl KeyboardTypoRule {
+    /// Rule over an unweighted vocabulary, for any language
+    pub fn new<S: AsRef<str>>(model: TypoModel, vocabulary: impl IntoIterator<Item = S>) -> Self {
+        let mut lexicon = Lexicon::new(Language::Unknown);
+        for word in vocabulary {
+            lexicon.insert(word.as_ref(), 1);
+        }
+        Self::with_lexicon(model, Arc::new(lexicon))
+    }
+
+    /// Rule over one lexicon, for its language
+    pub fn with_lexicon(model: TypoModel, lexicon: Arc<Lexicon>) -> Self {
+        let mut lexicons = Lexicons::empty();
+        lexicons.insert(lexicon);
+        Self::with_lexicons(model, lexicons)
+    }
+
+    pub fn with_lexicons(model: TypoModel, lexicons: Lexicons) -> Self {
+        Self { model, lexicons, min_log_likelihood: -7.0, min_margin: 2.5 }
+    }
+
+    /// Most plausible word for `typed` in `language`'s lexicon, with its
+    /// posterior; `None` unless it is likely enough and clearly ahead of the runner-up
+    pub fn best_candidate(&self, typed: &str, language: &Language) -> Option<(&str, f32)> {
+        let suggestions = self.lexicons.get(language)?.lookup(typed, lexicon::MAX_DISTANCE);
+        let ranked = self.model.rank_with_priors(typed, suggestions.iter().map(|s| (s.word, s.frequency as f32)));
+        let best = ranked.first().filter(|c| self.model.log_likelihood(typed, c.word) >= self.min_log_likelihood)?;
+        if ranked.get(1).is_some_and(|runner_up| best.score - runner_up.score < self.min_margin) {
+            return None;
+        }
+        Some((best.word, best.posterior))
+    }
+}
+
+impl NoiseRule for KeyboardTypoRule {
+    fn name(&self) -> &'static str {
+        "keyboard-typo"
+    }
+
+    fn priority(&self) -> i32 {
+        3
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let lexicon = self.lexicons.get(&input.language)?;
+        let window = input.search_range()?;
+        words(input.text, window.clone(), input.caret).rev().find_map(|range| {
+            let range = joined_by_apostrophes(input.text, range);
+            // A contraction reaching the caret or leaving the window is not finished or not ours
+            if range.start < window.start || range.end > window.end || range.end >= input.caret {
+                return None;
+            }
+            let typed = input.text[range.clone()].replace('’', "'");
+            let letters = typed.chars().filter(|&c| c != '\'').count();
+            let plain = typed.chars().all(|c| c.is_lowercase() || c == '\'') && letters >= 3;
+            if !plain || lexicon.contains(&typed) {
+                return None;
+            }
+            let (candidate, posterior) = self.best_candidate(&typed, &input.language)?;
+            let candidate = if input.text[range.clone()].contains('’') { candidate.replace('\'', "’") } else { candidate.to_string() };
+            Some(proposal(self.name(), range, &candidate, posterior))
+        })
+    }
+
+    fn set_keyboard_layout(&mut self, layout: KeyboardLayout) {
+        self.model.layout = layout;
+    }
+}
+
+/// Spacing around commas and periods ("word ,next" → "word, next")
+///
+/// Spacing before `?!:;` is language-specific and left to rule packs.
+pub struct PunctuationRule;
+
+impl NoiseRule for PunctuationRule {
+    fn name(&self) -> &'static str {
+        "punctuation-normalization"
+    }
+
+    fn priority(&self) -> i32 {
+        2
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        let text = input.text;
+        let chars: Vec<(usize, char)> = text[window.clone()].char_indices().map(|(i, c)| (window.start + i, c)).collect();
+        let mut best: Option<(Range<usize>, &str)> = None;
+        for (k, &(pos, c)) in chars.iter().enumerate() {
+            if !matches!(c, ',' | '.') {
+                continue;
+            }
+            let prev = text[..pos].chars().next_back();
+            let next = text[pos + 1..].chars().next();
+            // "word ," → "word,"; skip ellipses and ".5"
+            if prev == Some(' ') && next != Some('.') && !next.is_some_and(|n| n.is_ascii_digit()) {
+                let space_start = text[..pos].trim_end_matches(' ').len();
+                let before = text[..space_start].chars().next_back();
+                if before.is_some_and(char::is_alphanumeric) && space_start >= window.start {
+                    best = Some((space_start..pos, ""));
+                }
+            }
+            // "one,two" → "one, two" (letters only, so "1,000" is left alone)
+            if c == ',' && prev.is_some_and(char::is_alphabetic) && next.is_some_and(char::is_alphabetic) && k + 1 < chars.len() {
+                best = Some((pos + 1..pos + 1, " "));
+            }
+        }
+        let (range, replacement) = best?;
+        Some(proposal(self.name(), range, replacement, 1.0))
+    }
+}
+
+pub(crate) fn proposal(rule: &'static str, range: Range<usize>, replacement: &str, confidence: f32) -> NoiseProposal {
+    NoiseProposal { rule, edit: TextEdit::new(range, replacement), confidence }
+}
+
+/// Byte ranges of whole word tokens inside `window`
+///
+/// A word touching either window edge is skipped when it continues past
+/// it; a word ending at the caret is still being typed and never counts.
+pub(crate) fn words(text: &str, window: Range<usize>, caret: usize) -> impl DoubleEndedIterator<Item = Range<usize>> + '_ {
+    let start = window.start;
+    let slice = &text[window.clone()];
+    let mut out = Vec::new();
+    let mut word_start: Option<usize> = None;
+    for (i, c) in slice.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        match (is_word, word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(s)) => {
+                out.push(start + s..start + i);
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = word_start {
+        let end = window.end;
+        let cut = text[end..].chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_');
+        if !cut && end < caret {
+            out.push(start + s..end);
+        }
+    }
+    if out.first().is_some_and(|r| r.start == start && text[..start].chars().next_back().is_some_and(char::is_alphanumeric)) {
+        out.remove(0);
+    }
+    out.into_iter()
+}
+
+/// `range` widened over apostrophes joining it to letters ("don" in "don't")
+fn joined_by_apostrophes(text: &str, mut range: Range<usize>) -> Range<usize> {
+    let is_apostrophe = |c: char| matches!(c, '\'' | '’');
+    loop {
+        let mut before = text[..range.start].char_indices().rev();
+        match (before.next(), before.next()) {
+            (Some((i, a)), Some((_, l))) if is_apostrophe(a) && l.is_alphabetic() => {
+                range.start = text[..i].trim_end_matches(char::is_alphabetic).len();
+            }
+            _ => break,
+        }
+    }
+    loop {
+        let mut after = text[range.end..].chars();
+        match (after.next(), after.next()) {
+            (Some(a), Some(l)) if is_apostrophe(a) && l.is_alphabetic() => {
+                let rest = &text[range.end + a.len_utf8()..];
+                range.end = text.len() - rest.trim_start_matches(char::is_alphabetic).len();
+            }
+            _ => break,
+        }
+    }
+    range
+}
+
+pub(crate) fn match_capital(original: &str, replacement: &str) -> String {
+    if !original.chars().next().is_some_and(char::is_uppercase) {
+        return replacement.to_string();
+    }
+    let mut chars = replacement.chars();
+    chars.next().map(|f| f.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
@@ -0,0 +1,338 @@
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  R U L E   P A C K S  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Per-language noise rules, loaded from data files.          ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Language-specific noise rules keyed by Language::code():
+           English typos and contractions, French spacing, German
+           nouns, Spanish ¿¡
+  • WHY  ▸ REQ-LANGUAGE-GATING leaves non-English text with Noise
+           only; packs make that stage useful for those users
+  • HOW  ▸ JSON packs (built-ins embedded from data/rules) turn
+           into NoiseRules the registry runs for the input language
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::noise::{match_capital, proposal, words, NoiseInput, NoiseProposal, NoiseRule};
+use crate::language_detection::Language;
+
+/// Built-in packs, embedded at compile time
+const BUILTIN: &[&str] = &[
+    include_str!("../../data/rules/en.json"),
+    include_str!("../../data/rules/es.json"),
+    include_str!("../../data/rules/fr.json"),
+    include_str!("../../data/rules/de.json"),
+];
+
+/// Space required before some punctuation (French `?!:;`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpaceBefore {
+    pub chars: String,
+    /// Inserted when missing; a plain space already there is swapped for it
+    pub space: String,
+}
+
+/// One language's rules, as stored in `data/rules/<code>.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RulePack {
+    /// `Language::code()` the pack applies to
+    pub language: String,
+    /// Whole-word replacements (lowercase keys), capital preserved
+    #[serde(default)]
+    pub words: HashMap<String, String>,
+    /// Literal (pattern, replacement) pairs, may span spaces (" si " → " is ")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub substitutions: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub space_before: Option<SpaceBefore>,
+    /// Nouns capitalised wherever they are typed lowercase
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nouns: Vec<String>,
+    /// (opening, closing) pairs; a sentence ending in `closing` gets `opening` at its start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inverted_marks: Vec<(char, char)>,
+}
+
+impl RulePack {
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let mut pack: RulePack = serde_json::from_str(json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        pack.language = pack.language.to_ascii_lowercase();
+        Ok(pack)
+    }
+
+    /// The pack's noise rules
+    pub fn rules(&self) -> Vec<Box<dyn NoiseRule>> {
+        let mut rules: Vec<Box<dyn NoiseRule>> = Vec::new();
+        if !self.words.is_empty() {
+            let words = self.words.iter().map(|(k, v)| (k.to_lowercase(), v.clone())).collect();
+            rules.push(Box::new(PackWordRule { words }));
+        }
+        if !self.substitutions.is_empty() {
+            rules.push(Box::new(PackSubstitutionRule { substitutions: self.substitutions.clone() }));
+        }
+        if !self.nouns.is_empty() {
+            rules.push(Box::new(NounCapitalizationRule { nouns: self.nouns.iter().map(|n| n.to_lowercase()).collect() }));
+        }
+        if let Some(space_before) = &self.space_before {
+            rules.push(Box::new(SpaceBeforeRule { space_before: space_before.clone() }));
+        }
+        if !self.inverted_marks.is_empty() {
+            rules.push(Box::new(InvertedMarkRule { pairs: self.inverted_marks.clone() }));
+        }
+        rules
+    }
+}
+
+/// Loaded packs and their rules, keyed by language code
+#[derive(Default)]
+pub struct RulePacks {
+    packs: HashMap<String, (RulePack, Vec<Box<dyn NoiseRule>>)>,
+}
+
+impl RulePacks {
+    /// No packs at all
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// The packs shipped in data/rules
+    pub fn builtin() -> Self {
+        let mut packs = Self::empty();
+        for json in BUILTIN {
+            packs.insert(RulePack::from_json(json).expect("built-in rule pack is valid JSON"));
+        }
+        packs
+    }
+
+    /// Add a pack, replacing any pack for the same language
+    pub fn insert(&mut self, pack: RulePack) {
+        let rules = pack.rules();
+        self.packs.insert(pack.language.clone(), (pack, rules));
+    }
+
+    pub fn remove(&mut self, code: &str) -> Option<RulePack> {
+        self.packs.remove(&code.to_ascii_lowercase()).map(|(pack, _)| pack)
+    }
+
+    pub fn get(&self, language: &Language) -> Option<&RulePack> {
+        self.packs.get(language.code()).map(|(pack, _)| pack)
+    }
+
+    /// Language codes with a pack, sorted
+    pub fn codes(&self) -> Vec<&str> {
+        let mut codes: Vec<&str> = self.packs.keys().map(String::as_str).collect();
+        codes.sort_unstable();
+        codes
+    }
+
+    /// Rules for `language`; empty when it has no pack
+    pub fn rules_for(&self, language: &Language) -> &[Box<dyn NoiseRule>] {
+        self.packs.get(language.code()).map_or(&[], |(_, rules)| rules.as_slice())
+    }
+
+    /// Load one pack file; returns its language code
+    pub fn load_file(&mut self, path: &Path) -> io::Result<String> {
+        let pack = RulePack::from_json(&fs::read_to_string(path)?)?;
+        let code = pack.language.clone();
+        self.insert(pack);
+        Ok(code)
+    }
+
+    /// Load every `*.json` in `dir` (overriding built-ins); returns the codes loaded
+    pub fn load_dir(&mut self, dir: &Path) -> io::Result<Vec<String>> {
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        paths.iter().map(|p| self.load_file(p)).collect()
+    }
+}
+
+/// Whole-word replacements from a pack (contractions, missing accents)
+pub struct PackWordRule {
+    pub words: HashMap<String, String>,
+}
+
+impl NoiseRule for PackWordRule {
+    fn name(&self) -> &'static str {
+        "pack-words"
+    }
+
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        words(input.text, window, input.caret).rev().find_map(|range| {
+            let word = &input.text[range.clone()];
+            let replacement = self.words.get(&word.to_lowercase())?;
+            Some(proposal(self.name(), range, &match_capital(word, replacement), 0.95))
+        })
+    }
+}
+
+/// Literal substitutions from a pack; the rightmost match in the window wins.
+/// A pattern starting or ending on a letter or digit only matches whole words,
+/// so "form " never fires inside "platform ".
+pub struct PackSubstitutionRule {
+    pub substitutions: Vec<(String, String)>,
+}
+
+impl NoiseRule for PackSubstitutionRule {
+    fn name(&self) -> &'static str {
+        "pack-substitutions"
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        let search = &input.text[window.clone()];
+        let (start, pattern, replacement) = self
+            .substitutions
+            .iter()
+            .filter_map(|(p, r)| {
+                let mut found = search.rmatch_indices(p.as_str()).map(|(i, _)| window.start + i);
+                found.find(|&i| whole_words(input.text, i..i + p.len())).map(|i| (i, p, r))
+            })
+            .max_by_key(|(i, _, _)| *i)?;
+        Some(proposal(self.name(), start..start + pattern.len(), replacement, 0.95))
+    }
+}
+
+/// A match that begins (ends) on a letter or digit must not continue a word before
+/// (after) it; a word-final match also needs the word to be finished
+fn whole_words(text: &str, range: Range<usize>) -> bool {
+    let matched = &text[range.clone()];
+    let word_char = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    if word_char(matched.chars().next()) && word_char(text[..range.start].chars().next_back()) {
+        return false;
+    }
+    if word_char(matched.chars().next_back()) {
+        return text[range.end..].chars().next().is_some_and(|c| !c.is_alphanumeric());
+    }
+    true
+}
+
+/// Capitalise known nouns (German); only all-lowercase words are touched
+pub struct NounCapitalizationRule {
+    pub nouns: HashSet<String>,
+}
+
+impl NoiseRule for NounCapitalizationRule {
+    fn name(&self) -> &'static str {
+        "pack-noun-capitalization"
+    }
+
+    fn priority(&self) -> i32 {
+        1
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        words(input.text, window, input.caret).rev().find_map(|range| {
+            let word = &input.text[range.clone()];
+            if !self.nouns.contains(word) {
+                return None;
+            }
+            let mut chars = word.chars();
+            let capitalised: String = chars.next()?.to_uppercase().chain(chars).collect();
+            Some(proposal(self.name(), range, &capitalised, 0.85))
+        })
+    }
+}
+
+/// Space before punctuation such as French `?!:;` ("Quoi?" → "Quoi ?")
+pub struct SpaceBeforeRule {
+    pub space_before: SpaceBefore,
+}
+
+impl NoiseRule for SpaceBeforeRule {
+    fn name(&self) -> &'static str {
+        "pack-space-before"
+    }
+
+    fn priority(&self) -> i32 {
+        2
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        let text = input.text;
+        let space = self.space_before.space.as_str();
+        let mut best = None;
+        for (i, c) in text[window.clone()].char_indices() {
+            let pos = window.start + i;
+            if !self.space_before.chars.contains(c) {
+                continue;
+            }
+            // Only punctuation closing a word, so "10:30" and "http://" are left alone
+            let next = text[pos + c.len_utf8()..].chars().next();
+            if next.is_some_and(|n| !n.is_whitespace() && !self.space_before.chars.contains(n)) {
+                continue;
+            }
+            let prev = text[..pos].chars().next_back();
+            if prev.is_some_and(|p| p.is_alphanumeric() || matches!(p, ')' | '»' | '"')) {
+                best = Some(pos..pos);
+            } else if prev == Some(' ') && space != " " {
+                let before = text[..pos - 1].chars().next_back();
+                if before.is_some_and(|b| !b.is_whitespace()) && pos > window.start {
+                    best = Some(pos - 1..pos);
+                }
+            }
+        }
+        Some(proposal(self.name(), best?, space, 0.9))
+    }
+}
+
+/// Opening `¿`/`¡` for a sentence that ends in `?`/`!` without one
+pub struct InvertedMarkRule {
+    pub pairs: Vec<(char, char)>,
+}
+
+impl NoiseRule for InvertedMarkRule {
+    fn name(&self) -> &'static str {
+        "pack-inverted-marks"
+    }
+
+    fn priority(&self) -> i32 {
+        2
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let window = input.search_range()?;
+        let text = input.text;
+        let mut best = None;
+        for (i, c) in text[window.clone()].char_indices() {
+            let pos = window.start + i;
+            let Some(&(open, _)) = self.pairs.iter().find(|(_, close)| *close == c) else { continue };
+            let start = sentence_start(text, pos);
+            let sentence = &text[start..pos];
+            if !sentence.contains(open) && sentence.chars().any(char::is_alphabetic) {
+                best = Some((start, open));
+            }
+        }
+        let (start, open) = best?;
+        Some(proposal(self.name(), start..start, &open.to_string(), 0.9))
+    }
+}
+
+/// Byte offset where the sentence containing `pos` starts
+fn sentence_start(text: &str, pos: usize) -> usize {
+    let before = &text[..pos];
+    let boundary = before.rfind(['.', '?', '!', '\n']).map_or(0, |i| i + 1);
+    // Punctuation ending the previous sentence may run on ("?!"); skip it and the spacing
+    boundary + before[boundary..].len() - before[boundary..].trim_start().len()
+}
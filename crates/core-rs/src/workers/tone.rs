@@ -1,13 +1,244 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   S T U B  ░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Stub for workers/$f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real implementation
+/*╔══════════════════════════════════════════════════════════════╗
+  ║  ░  T O N E   W O R K E R  ░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░  ║
+  ║                                                              ║
+  ║   Baseline tone detection and selectable target tone.        ║
+  ║                                                              ║
+  ╚══════════════════════════════════════════════════════════════╝
+  • WHAT ▸ Tone stage (port of engines/toneTransformer.ts)
+  • WHY  ▸ REQ-TONE-TRANSFORMER; runs on long pauses
+  • HOW  ▸ Measure the document, then propose phrase rewrites
+           toward the target; edits end before the caret
 */
 
-#[allow(dead_code)]
-pub fn _stub_worker_tone() {}
+use std::ops::Range;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::diff::TextEdit;
+use crate::fragment::FragmentExtractor;
+use crate::offsets::floor_char_boundary;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ToneTarget {
+    #[default]
+    None,
+    Casual,
+    Professional,
+}
+
+impl ToneTarget {
+    /// Parse "None" / "Casual" / "Professional" (any case)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(ToneTarget::None),
+            "casual" => Some(ToneTarget::Casual),
+            "professional" => Some(ToneTarget::Professional),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneTarget::None => "None",
+            ToneTarget::Casual => "Casual",
+            ToneTarget::Professional => "Professional",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ToneSettings {
+    pub enabled: bool,
+    pub target: ToneTarget,
+}
+
+impl ToneSettings {
+    /// Enabled with a target other than `None`
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.target != ToneTarget::None
+    }
+}
+
+lazy_static! {
+    static ref GLOBAL_TONE: Mutex<ToneSettings> = Mutex::new(ToneSettings::default());
+}
+
+/// Process-wide tone settings (set by hosts through `mind_type_set_tone`)
+pub fn global_tone() -> ToneSettings {
+    GLOBAL_TONE.lock().map(|t| *t).unwrap_or_default()
+}
+
+pub fn set_global_tone(settings: ToneSettings) {
+    if let Ok(mut tone) = GLOBAL_TONE.lock() {
+        *tone = settings;
+    }
+}
+
+/// Document tone; every axis is 0..1
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ToneVector {
+    /// Higher = more formal
+    pub formality: f32,
+    /// Higher = friendlier / more casual (TS `friendliness`)
+    pub warmth: f32,
+    /// Share of contractible phrases written contracted
+    pub contractions: f32,
+    /// Hedges ("maybe", "I think", …) per sentence
+    pub hedging: f32,
+}
+
+/// (contracted, expanded) pairs in lowercase; apostrophes are matched in both styles
+const CONTRACTIONS: &[(&str, &str)] = &[
+    ("can't", "cannot"),
+    ("won't", "will not"),
+    ("don't", "do not"),
+    ("doesn't", "does not"),
+    ("isn't", "is not"),
+    ("it's", "it is"),
+    ("i'm", "i am"),
+    ("you're", "you are"),
+    ("we're", "we are"),
+    ("they're", "they are"),
+    ("i'll", "i will"),
+    ("we'll", "we will"),
+];
+
+/// Contractions the Casual target introduces (a light touch, like the TS planner)
+const CASUAL_CONTRACTIONS: &[&str] = &["it's", "we're", "you're", "i'm", "don't"];
+
+const SLANG: &[(&str, &str)] = &[("gonna", "going to"), ("wanna", "want to"), ("gotta", "have to"), ("yup", "yes"), ("nah", "no")];
+const FRIENDLY: &[&str] = &["cool", "hey", "awesome", "thanks"];
+const HEDGES: &[&str] = &["maybe", "perhaps", "probably", "possibly", "i think", "kind of", "sort of", "i guess", "might"];
+
+/// Baseline tone of `document` (same heuristics as `detectBaseline`, plus contraction share and hedging)
+pub fn detect_baseline(document: &str) -> ToneVector {
+    let len = document.len().max(1) as f32;
+    let commas = document.chars().filter(|c| matches!(c, ',' | ';')).count() as f32 / len;
+    let contracted: usize = CONTRACTIONS.iter().map(|(c, _)| find_phrase(document, 0..document.len(), c).len()).sum();
+    let expanded: usize = CONTRACTIONS.iter().map(|(_, e)| find_phrase(document, 0..document.len(), e).len()).sum();
+    let slang: usize = SLANG.iter().map(|(s, _)| s).chain(FRIENDLY).map(|s| find_phrase(document, 0..document.len(), s).len()).sum();
+    let hedges: usize = HEDGES.iter().map(|h| find_phrase(document, 0..document.len(), h).len()).sum();
+    let exclamations = document.matches('!').count();
+    let sentences = FragmentExtractor::new().segment(document).len().max(1);
+
+    let (contracted, slang) = (contracted as f32, slang as f32);
+    ToneVector {
+        formality: (0.6 + commas * 5.0 - contracted * 0.05 - slang * 0.05).clamp(0.0, 1.0),
+        warmth: (0.4 + contracted * 0.05 + slang * 0.08 + exclamations as f32 * 0.03).clamp(0.0, 1.0),
+        contractions: if contracted as usize + expanded == 0 { 0.0 } else { contracted / (contracted + expanded as f32) },
+        hedging: (hedges as f32 / sentences as f32).clamp(0.0, 1.0),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToneProposal {
+    /// Byte range of the document; always ends before the caret
+    pub edit: TextEdit,
+    pub confidence: f32,
+}
+
+pub struct ToneWorker {
+    pub settings: ToneSettings,
+    /// Sentences before the caret in scope (10 on CPU, up to 20 on WebGPU/WASM)
+    pub scope_sentences: usize,
+}
+
+impl Default for ToneWorker {
+    fn default() -> Self {
+        Self::new(ToneSettings::default())
+    }
+}
+
+impl ToneWorker {
+    pub fn new(settings: ToneSettings) -> Self {
+        Self { settings, scope_sentences: 10 }
+    }
+
+    /// Rewrites moving the text before `caret` toward the target tone
+    pub fn plan(&self, text: &str, caret: usize) -> Vec<ToneProposal> {
+        if !self.settings.is_active() {
+            return Vec::new();
+        }
+        let baseline = detect_baseline(text);
+        let scope = self.scope(text, caret);
+        let mut proposals = Vec::new();
+        match self.settings.target {
+            ToneTarget::Professional if baseline.formality < 0.7 => {
+                let confidence = 0.8 + (0.7 - baseline.formality) * 0.2;
+                for (from, to) in CONTRACTIONS.iter().chain(SLANG) {
+                    for range in find_phrase(text, scope.clone(), from) {
+                        let replacement = match_case(&text[range.clone()], to);
+                        proposals.push(ToneProposal { edit: TextEdit::new(range, replacement), confidence });
+                    }
+                }
+            }
+            ToneTarget::Casual if baseline.warmth < 0.6 => {
+                let apostrophe = if text[..scope.end].contains('’') { "’" } else { "'" };
+                let confidence = 0.75 + (0.6 - baseline.warmth) * 0.2;
+                for (contracted, expanded) in CONTRACTIONS.iter().filter(|(c, _)| CASUAL_CONTRACTIONS.contains(c)) {
+                    for range in find_phrase(text, scope.clone(), expanded) {
+                        let replacement = match_case(&text[range.clone()], &contracted.replace('\'', apostrophe));
+                        proposals.push(ToneProposal { edit: TextEdit::new(range, replacement), confidence });
+                    }
+                }
+            }
+            _ => {}
+        }
+        proposals.sort_by_key(|p| p.edit.range.start);
+        proposals
+    }
+
+    /// The last `scope_sentences` sentences before the caret, minus any word still being typed
+    fn scope(&self, text: &str, caret: usize) -> Range<usize> {
+        let caret = floor_char_boundary(text, caret);
+        let sentences = FragmentExtractor::new().segment(&text[..caret]);
+        let first = sentences.len().saturating_sub(self.scope_sentences.max(1));
+        let start = sentences.get(first).map_or(caret, |s| s.range.start);
+        let end = text[..caret].trim_end_matches(|c: char| c.is_alphanumeric() || c == '\'' || c == '’').len();
+        start..end.max(start)
+    }
+}
+
+/// Whole-word, case-insensitive matches of an ASCII `phrase` inside `range`;
+/// "'" in the phrase also matches "’"
+fn find_phrase(text: &str, range: Range<usize>, phrase: &str) -> Vec<Range<usize>> {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '\'' || c == '’');
+    let mut out = Vec::new();
+    for (i, _) in text[range.clone()].char_indices() {
+        let start = range.start + i;
+        if is_word(text[..start].chars().next_back()) {
+            continue;
+        }
+        let mut end = start;
+        let mut rest = text[start..range.end].chars();
+        let matched = phrase.chars().all(|p| match rest.next() {
+            Some(c) if c.to_ascii_lowercase() == p || (c == '’' && p == '\'') => {
+                end += c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched && !is_word(text[end..].chars().next()) {
+            out.push(start..end);
+        }
+    }
+    out
+}
+
+/// Copy the capitalisation of `original`'s first letter onto `replacement`
+fn match_case(original: &str, replacement: &str) -> String {
+    let upper = original.chars().next().is_some_and(char::is_uppercase);
+    let mut out = String::with_capacity(replacement.len());
+    for (i, c) in replacement.char_indices() {
+        // Standalone "i" ("i am", "i'm") is always capitalised
+        let pronoun = c == 'i' && replacement[i + 1..].chars().next().is_none_or(|n| !n.is_alphabetic()) && (i == 0 || replacement[..i].ends_with(' '));
+        if (i == 0 && upper) || pronoun {
+            out.extend(c.to_uppercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -1,13 +1,76 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use core_rs::active_region::ActiveRegionPolicy;
+
+fn policy(target_words: usize) -> ActiveRegionPolicy {
+    let mut policy = ActiveRegionPolicy::new(target_words);
+    policy.snap_words = 0;
+    policy
+}
+
+#[test]
+fn walks_back_target_words_and_stops_at_caret() {
+    let text = "one two three four five six";
+    let caret = text.find(" six").unwrap();
+    let region = policy(3).calculate_region(text, caret);
+    assert_eq!(&text[region.render_range()], "three four five");
+    assert_eq!(region.word_count, 3);
+    assert_eq!(region.end, caret);
+}
+
+#[test]
+fn never_panics_inside_multibyte_chars() {
+    let text = "naïve café 👍🏽 日本語のテキスト";
+    for caret in 0..=text.len() + 3 {
+        let region = ActiveRegionPolicy::default().calculate_region(text, caret);
+        assert!(region.end <= caret.min(text.len()));
+        assert!(text.is_char_boundary(region.start) && text.is_char_boundary(region.end));
+        assert!(region.context.start <= region.start && region.context.end == region.end);
+    }
+}
+
+#[test]
+fn punctuation_and_spaces_are_not_words() {
+    let text = "hi , there ... friend";
+    let region = policy(2).calculate_region(text, text.len());
+    assert_eq!(&text[region.render_range()], "there ... friend");
+}
+
+#[test]
+fn does_not_cross_line_breaks() {
+    let text = "first line words\nsecond";
+    let region = policy(10).calculate_region(text, text.len());
+    assert_eq!(&text[region.render_range()], "second");
+}
+
+#[test]
+fn snaps_to_nearby_sentence_start() {
+    let text = "The old sentence ends here. New one starts now";
+    let mut snapping = ActiveRegionPolicy::new(6);
+    snapping.snap_words = 2;
+    // Six words would reach back to "ends"; the sentence start is two words away
+    let region = snapping.calculate_region(text, text.len());
+    assert_eq!(&text[region.render_range()], "New one starts now");
+
+    // Extends backwards too when the sentence start is just out of reach
+    let region = snapping.calculate_region("Short one. Alpha beta gamma delta epsilon zeta eta", 51);
+    assert_eq!(region.word_count, 7);
+
+    // Too far away: no snap
+    let region = policy(6).calculate_region(text, text.len());
+    assert_eq!(&text[region.render_range()], "ends here. New one starts now");
+}
+
+#[test]
+fn context_extends_left_without_splitting_words() {
+    let text = "alpha beta gamma delta epsilon zeta";
+    let mut p = policy(2);
+    p.context_left_bytes = 9;
+    let region = p.calculate_region(text, text.len());
+    assert_eq!(&text[region.render_range()], "epsilon zeta");
+    assert_eq!(&text[region.context.clone()], "delta epsilon zeta");
+}
+
+#[test]
+fn empty_before_caret() {
+    let region = ActiveRegionPolicy::default().calculate_region("   hello", 2);
+    assert_eq!((region.start, region.end, region.word_count), (2, 2, 0));
+}
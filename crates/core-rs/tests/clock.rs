@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use core_rs::clock::{Clock, ManualClock, ScaledClock};
+use core_rs::pause_timer::PauseTimer;
+use core_rs::scheduler::{Stage, SweepScheduler};
+
+#[test]
+fn pause_timer_follows_manual_clock() {
+    let clock = ManualClock::new(10_000);
+    let mut timer = PauseTimer::with_clock(300, Arc::new(clock.clone()));
+    timer.record_activity();
+    clock.advance(300);
+    assert!(!timer.is_paused(), "threshold is exclusive");
+    clock.advance(1);
+    assert!(timer.is_paused());
+    timer.record_activity();
+    assert!(!timer.is_paused());
+}
+
+#[test]
+fn sweep_scheduler_follows_manual_clock() {
+    let clock = ManualClock::new(0);
+    let mut scheduler = SweepScheduler::with_clock(500, Arc::new(clock.clone()));
+    assert!(scheduler.due_stages().is_empty(), "inactive scheduler never runs");
+    scheduler.start();
+    assert_eq!(scheduler.due_stages(), [Stage::Noise]);
+    clock.advance(500);
+    assert!(scheduler.is_paused());
+    assert_eq!(scheduler.due_stages(), [Stage::Noise, Stage::Context]);
+}
+
+#[test]
+fn scaled_clock_runs_faster() {
+    let base = ManualClock::new(0);
+    let scaled = ScaledClock::new(Arc::new(base.clone()), 5_000, 10.0);
+    assert_eq!(scaled.now_ms(), 5_000);
+    base.advance(250);
+    assert_eq!(scaled.now_ms(), 7_500);
+}
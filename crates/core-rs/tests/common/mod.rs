@@ -0,0 +1,18 @@
+use core_rs::caret_monitor::{CaretEvent, EventKind, FieldKind, InputModality, SelectionFacet};
+
+/// Keyboard insert at UTF-16 `caret` (also the text length) at time `t`
+pub fn typed(caret: usize, t: u64) -> CaretEvent {
+    let caret = caret as u32;
+    CaretEvent {
+        kind: EventKind::Input,
+        timestamp_ms: t,
+        caret,
+        text_len: caret,
+        selection: SelectionFacet { collapsed: true, start: caret, end: caret },
+        input_modality: InputModality::Keyboard,
+        field_kind: FieldKind::TextArea,
+        ime_active: false,
+        blocked: false,
+        input_type: Some("insertText".into()),
+    }
+}
@@ -1,13 +1,168 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use core_rs::confidence::{
+    apply_thresholds, compute_confidence, compute_dynamic_thresholds, compute_input_fidelity, distance_from_caret,
+    load_learned_state, save_learned_state, temporal_decay, AdaptiveThresholds, ConfidenceInputs,
+    DynamicThresholdsInput, EditType, GateDecision, GateThresholds,
+};
+use core_rs::staging_buffer::ProposalState;
+
+fn inputs(fidelity: f32, quality: f32, coherence: f32, decay: f32) -> ConfidenceInputs {
+    ConfidenceInputs {
+        input_fidelity: fidelity,
+        transformation_quality: quality,
+        context_coherence: coherence,
+        temporal_decay: decay,
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-5
+}
+
+#[test]
+fn input_fidelity_counts_letters_and_digits() {
+    assert_eq!(compute_input_fidelity("hello world 42"), 1.0);
+    assert_eq!(compute_input_fidelity("ab#$"), 0.5);
+    assert_eq!(compute_input_fidelity("   "), 0.0);
+    assert_eq!(compute_input_fidelity("héllo"), 1.0);
+}
+
+#[test]
+fn combined_score_is_a_clamped_weighted_sum() {
+    let score = compute_confidence(inputs(1.0, 1.0, 1.0, 1.0));
+    assert!(close(score.combined, 1.0));
+    let score = compute_confidence(inputs(1.0, 0.0, 0.0, 0.0));
+    assert!(close(score.combined, 0.3));
+    let score = compute_confidence(inputs(0.0, 1.0, 0.0, 0.0));
+    assert!(close(score.combined, 0.4));
+    let score = compute_confidence(inputs(2.0, f32::NAN, -1.0, 1.0));
+    assert_eq!(score.inputs, inputs(1.0, 0.0, 0.0, 1.0));
+    assert!(close(score.combined, 0.4));
+}
+
+#[test]
+fn temporal_decay_halves_each_half_life() {
+    assert_eq!(temporal_decay(0, 1_000), 1.0);
+    assert!(close(temporal_decay(1_000, 1_000), 0.5));
+    assert!(close(temporal_decay(2_000, 1_000), 0.25));
+    assert_eq!(temporal_decay(5, 0), 0.0);
+}
+
+#[test]
+fn gate_decides_hold_commit_discard() {
+    let t = GateThresholds::default();
+    let strong = compute_confidence(inputs(1.0, 0.95, 0.8, 1.0));
+    assert_eq!(apply_thresholds(&strong, &t, false), GateDecision::Commit);
+    let weak = compute_confidence(inputs(0.2, 0.1, 0.1, 0.5));
+    assert_eq!(apply_thresholds(&weak, &t, false), GateDecision::Discard);
+    // Garbled input holds even with a good rewrite
+    let garbled = compute_confidence(inputs(0.5, 1.0, 1.0, 1.0));
+    assert_eq!(apply_thresholds(&garbled, &t, false), GateDecision::Hold);
+    // Tone also needs τ_tone transformation quality
+    let tone = compute_confidence(inputs(1.0, 0.7, 1.0, 1.0));
+    assert_eq!(apply_thresholds(&tone, &t, false), GateDecision::Commit);
+    assert_eq!(apply_thresholds(&tone, &t, true), GateDecision::Hold);
+    assert_eq!(ProposalState::from(GateDecision::Discard), ProposalState::Discard);
+}
+
+#[test]
+fn distance_is_measured_from_the_end_of_the_range() {
+    assert_eq!(distance_from_caret(20, &(5..8)), 12);
+    assert_eq!(distance_from_caret(6, &(5..8)), 0);
+    assert_eq!(distance_from_caret(2, &(5..8)), 0);
+}
+
+#[test]
+fn dynamic_thresholds_follow_edit_type_distance_and_undos() {
+    let at = |range, edit_type| compute_dynamic_thresholds(&DynamicThresholdsInput::new(100, range, edit_type));
+    let near = at(95..99, EditType::Context);
+    let far = at(0..10, EditType::Context);
+    assert!(near.commit > far.commit && near.input > far.input);
+    assert!(close(far.commit, 0.8));
+
+    let noise = at(0..10, EditType::Noise);
+    let tone = at(0..10, EditType::Tone);
+    let lm = at(0..10, EditType::Lm);
+    assert!(noise.commit < lm.commit && lm.commit < tone.commit);
+    assert!(noise.input < lm.input);
+
+    let mut undone = DynamicThresholdsInput::new(100, 0..10, EditType::Context);
+    undone.recent_rollbacks = 2;
+    undone.last_rollback_ms_ago = Some(0);
+    assert!(close(compute_dynamic_thresholds(&undone).commit, 0.85));
+    undone.last_rollback_ms_ago = Some(3_000);
+    assert!(close(compute_dynamic_thresholds(&undone).commit, 0.8), "boost fades over 3 s");
+}
+
+#[test]
+fn dynamic_thresholds_keep_their_invariants() {
+    let mut input = DynamicThresholdsInput::new(10, 9..10, EditType::Tone);
+    input.sensitivity = 50.0;
+    input.recent_rollbacks = 10;
+    input.last_rollback_ms_ago = Some(0);
+    input.base = GateThresholds { input: 0.9, commit: 0.5, tone: 1.5, discard: 0.95 };
+    let t = compute_dynamic_thresholds(&input);
+    assert!(t.commit <= 0.98);
+    assert!(t.input <= t.commit);
+    assert!(t.discard < t.input);
+    assert_eq!(t.tone, 1.0);
+}
+
+#[test]
+fn undo_soon_after_a_system_edit_raises_its_class_and_region() {
+    let mut adaptive = AdaptiveThresholds::default();
+    adaptive.record_applied(EditType::Tone, 10..20, 1_000);
+    assert_eq!(adaptive.record_undo(2_000), Some(EditType::Tone));
+
+    // Class and region both count
+    assert!(close(adaptive.boost(EditType::Tone, &(12..15), 2_000), 0.1));
+    // Same class elsewhere, or another class in the same region
+    assert!(close(adaptive.boost(EditType::Tone, &(900..905), 2_000), 0.05));
+    assert!(close(adaptive.boost(EditType::Noise, &(12..15), 2_000), 0.05));
+    assert_eq!(adaptive.boost(EditType::Noise, &(900..905), 2_000), 0.0);
+
+    let input = DynamicThresholdsInput::new(500, 12..15, EditType::Tone);
+    let raised = adaptive.thresholds(&input, 2_000);
+    assert!(close(raised.commit, compute_dynamic_thresholds(&input).commit + 0.1));
+
+    // Repeated undos are capped
+    for t in 0..10 {
+        adaptive.record_applied(EditType::Tone, 10..20, 3_000 + t);
+        adaptive.record_undo(3_000 + t);
+    }
+    assert!(close(adaptive.boost(EditType::Tone, &(10..20), 3_100), adaptive.config.max_boost));
+}
+
+#[test]
+fn late_undos_are_the_users_own_and_boosts_decay() {
+    let mut adaptive = AdaptiveThresholds::default();
+    adaptive.record_applied(EditType::Noise, 0..3, 0);
+    assert_eq!(adaptive.record_undo(60_000), None);
+    assert!(adaptive.record_undo(60_001).is_none(), "nothing left to blame");
+
+    adaptive.record_applied(EditType::Noise, 0..3, 0);
+    adaptive.record_undo(100);
+    let half_life = adaptive.config.half_life_ms;
+    let fresh = adaptive.boost(EditType::Noise, &(0..3), 100);
+    let later = adaptive.boost(EditType::Noise, &(0..3), 100 + half_life);
+    assert!(close(later, fresh / 2.0));
+}
+
+#[test]
+fn learned_state_round_trips_per_user() {
+    let mut adaptive = AdaptiveThresholds::default();
+    adaptive.record_applied(EditType::Context, 0..5, 0);
+    adaptive.record_undo(10);
+    let state = adaptive.learned("ana/../x", 10);
+    assert_eq!(state.classes, [(EditType::Context, 0.05)]);
+
+    let dir = std::env::temp_dir().join(format!("mindtype-adaptive-{}", std::process::id()));
+    save_learned_state(&dir, &state).unwrap();
+    assert_eq!(load_learned_state(&dir, "someone-else").unwrap(), None);
+    let loaded = load_learned_state(&dir, "ana/../x").unwrap().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let mut restored = AdaptiveThresholds::default();
+    restored.restore(&loaded, 5_000_000);
+    assert!(close(restored.boost(EditType::Context, &(400..401), 5_000_000), 0.05));
+    assert_eq!(restored.boost(EditType::Tone, &(400..401), 5_000_000), 0.0);
+}
@@ -0,0 +1,122 @@
+use core_rs::conflict::{
+    resolve_conflicts, ConflictResolver, DropReason, MergePolicy, Proposal, ProposalSource, ResolverConfig,
+};
+use core_rs::diff::TextEdit;
+
+fn p(source: ProposalSource, range: std::ops::Range<usize>, text: &str, confidence: f32, t: u64) -> Proposal {
+    Proposal { source, edit: TextEdit::new(range, text), confidence, timestamp_ms: t }
+}
+
+#[test]
+fn higher_priority_source_wins_and_the_loser_is_explained() {
+    let text = "teh cat sat";
+    let noise = p(ProposalSource::Noise, 0..3, "the", 0.8, 0);
+    let tone = p(ProposalSource::Tone, 0..7, "A cat", 0.99, 5);
+    let lm = p(ProposalSource::Lm, 8..11, "sits", 0.9, 0);
+    let out = resolve_conflicts(text, vec![tone.clone(), lm.clone(), noise.clone()]);
+    assert_eq!(out.accepted, [noise.clone(), lm]);
+    assert_eq!(out.dropped.len(), 1);
+    assert_eq!(out.dropped[0].reason, DropReason::Priority);
+    assert_eq!(out.dropped[0].winner.as_ref(), Some(&noise));
+    assert_eq!(out.dropped[0].to_string(), "tone 0..7 dropped: overlaps noise 0..3 (higher-priority source)");
+    assert_eq!(TextEdit::apply_all(text, &out.edits()).unwrap(), "the cat sits");
+}
+
+#[test]
+fn custom_precedence_reorders_sources() {
+    let config = ResolverConfig { precedence: vec![ProposalSource::Tone, ProposalSource::Noise], ..ResolverConfig::default() };
+    let out = ConflictResolver::new(config).resolve(
+        "teh cat",
+        vec![p(ProposalSource::Noise, 0..3, "the", 0.9, 0), p(ProposalSource::Tone, 0..7, "A cat", 0.9, 0)],
+    );
+    assert_eq!(out.accepted[0].source, ProposalSource::Tone);
+    // Unlisted sources rank last
+    let out = ConflictResolver::new(ResolverConfig { precedence: vec![ProposalSource::Tone], ..ResolverConfig::default() })
+        .resolve("teh", vec![p(ProposalSource::Noise, 0..3, "the", 0.99, 0), p(ProposalSource::Tone, 0..3, "The", 0.5, 0)]);
+    assert_eq!(out.accepted[0].source, ProposalSource::Tone);
+    assert_eq!(out.dropped[0].reason, DropReason::Priority);
+}
+
+#[test]
+fn policies_break_ties_within_a_source() {
+    let text = "i seen teh dog";
+    let long = p(ProposalSource::Context, 0..10, "I saw the", 0.7, 0);
+    let sure = p(ProposalSource::Context, 7..10, "the", 0.95, 0);
+
+    let out = resolve_conflicts(text, vec![sure.clone(), long.clone()]);
+    assert_eq!(out.accepted, std::slice::from_ref(&long));
+    assert_eq!(out.dropped[0].reason, DropReason::Shorter);
+
+    let config = ResolverConfig { policy: MergePolicy::PreferHigherConfidence, ..ResolverConfig::default() };
+    let out = ConflictResolver::new(config).resolve(text, vec![long, sure.clone()]);
+    assert_eq!(out.accepted, std::slice::from_ref(&sure));
+    assert_eq!(out.dropped[0].reason, DropReason::LowerConfidence);
+
+    // Same span and confidence: the newer proposal wins
+    let newer = p(ProposalSource::Context, 7..10, "a", 0.95, 10);
+    let out = resolve_conflicts(text, vec![sure, newer.clone()]);
+    assert_eq!(out.accepted, [newer]);
+    assert_eq!(out.dropped[0].reason, DropReason::Older);
+}
+
+#[test]
+fn compose_folds_nested_edits_into_the_winner() {
+    let config = ResolverConfig { policy: MergePolicy::Compose, ..ResolverConfig::default() };
+    let resolver = ConflictResolver::new(config);
+
+    // Capitalisation inside an untouched prefix of the tone rewrite
+    let text = "i'm gonna go";
+    let out = resolver.resolve(
+        text,
+        vec![p(ProposalSource::Noise, 0..9, "i am going to", 0.9, 0), p(ProposalSource::Context, 0..1, "I", 0.8, 0)],
+    );
+    assert!(out.dropped.is_empty());
+    assert_eq!(out.composed.len(), 1);
+    assert_eq!(out.accepted[0].confidence, 0.8);
+    assert_eq!(TextEdit::apply_all(text, &out.edits()).unwrap(), "I am going to go");
+
+    // Anchored at the end
+    let text = "teh cat";
+    let out = resolver.resolve(
+        text,
+        vec![p(ProposalSource::Noise, 0..7, "the cat", 0.9, 0), p(ProposalSource::Lm, 4..7, "dog", 0.9, 0)],
+    );
+    assert_eq!(TextEdit::apply_all(text, &out.edits()).unwrap(), "the dog");
+
+    // "teh" → "the" keeps the leading "t", so capitalising it still composes
+    let out = resolver.resolve(
+        text,
+        vec![p(ProposalSource::Noise, 0..3, "the", 0.9, 0), p(ProposalSource::Context, 0..1, "T", 0.9, 0)],
+    );
+    assert_eq!(TextEdit::apply_all(text, &out.edits()).unwrap(), "The cat");
+
+    // The winner rewrote the loser's text: nothing to compose
+    let out = resolver.resolve(
+        text,
+        vec![p(ProposalSource::Noise, 0..3, "the", 0.9, 0), p(ProposalSource::Context, 1..2, "E", 0.9, 0)],
+    );
+    assert_eq!(out.dropped.len(), 1);
+}
+
+#[test]
+fn invalid_ranges_are_dropped_with_a_reason() {
+    let out = resolve_conflicts("héllo", vec![p(ProposalSource::Noise, 2..3, "e", 0.9, 0), p(ProposalSource::Lm, 3..99, "", 0.9, 0)]);
+    assert!(out.accepted.is_empty());
+    assert!(out.dropped.iter().all(|d| d.reason == DropReason::InvalidRange && d.winner.is_none()));
+    assert_eq!(out.dropped[1].to_string(), "lm 3..99 dropped: invalid range");
+}
+
+#[test]
+fn adjacent_and_insertion_edits_compose_without_conflict() {
+    let text = "a b";
+    let out = resolve_conflicts(
+        text,
+        vec![
+            p(ProposalSource::Noise, 0..1, "A", 0.9, 0),
+            p(ProposalSource::Context, 1..1, ",", 0.9, 0),
+            p(ProposalSource::Tone, 1..2, "  ", 0.9, 0),
+        ],
+    );
+    assert!(out.dropped.is_empty());
+    assert_eq!(TextEdit::apply_all(text, &out.edits()).unwrap(), "A,  b");
+}
@@ -1,13 +1,80 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use core_rs::diff::TextEdit;
+use core_rs::engine::Correction;
+use core_rs::language_detection::Language;
+use core_rs::workers::context::{build_context_window, ContextWorker, RepairKind};
+
+fn repaired(text: &str, caret: usize) -> String {
+    let result = ContextWorker::default().propose(text, caret, &Language::English);
+    let edits: Vec<TextEdit> = result.proposals.into_iter().map(|p| p.edit).collect();
+    TextEdit::apply_all(text, &edits).unwrap()
+}
+
+#[test]
+fn window_holds_current_and_previous_sentences() {
+    let text = "One. Two. Three. Four is here. Five.";
+    let caret = text.find("is").unwrap();
+    let window = build_context_window(text, caret, 2);
+    let slice = |r: &std::ops::Range<usize>| &text[r.clone()];
+    assert_eq!(slice(&window.current), "Four is here.");
+    assert_eq!(window.previous.iter().map(slice).collect::<Vec<_>>(), ["Three.", "Two."]);
+    assert_eq!(window.next.iter().map(slice).collect::<Vec<_>>(), ["Five."]);
+    assert_eq!(slice(&window.editable(caret)), "Two. Three. Four ");
+
+    // Between sentences: nothing is current, everything before is previous
+    let window = build_context_window("One. Two. ", 10, 5);
+    assert!(window.current.is_empty());
+    assert_eq!(window.previous.len(), 2);
+}
+
+#[test]
+fn capitalises_sentence_starts_and_standalone_i() {
+    assert_eq!(repaired("hello there. so i went home", 27), "Hello there. So I went home");
+    assert_eq!(repaired("i think so", 10), "I think so");
+    // Not after abbreviations or initials
+    assert_eq!(repaired("Ask Dr. smith or e.g. bob or J. doe now", 39), "Ask Dr. smith or e.g. bob or J. doe now");
+    // iPhone-style words are left alone
+    assert_eq!(repaired("iPhone sales are up", 19), "iPhone sales are up");
+    // Standalone "i" is English-only
+    let result = ContextWorker::default().propose("Ja i nie", 8, &Language::Unknown);
+    assert!(result.proposals.is_empty());
+}
+
+#[test]
+fn removes_duplicated_words() {
+    assert_eq!(repaired("We saw the the cat today", 24), "We saw the cat today");
+    assert_eq!(repaired("What she had had was enough", 27), "What she had had was enough");
+}
+
+#[test]
+fn adds_missing_terminal_punctuation_to_finished_lines() {
+    assert_eq!(repaired("Notes from the meeting\nNext", 27), "Notes from the meeting.\nNext");
+    // Titles and the line still being typed are untouched
+    assert_eq!(repaired("Agenda\nWe met today", 19), "Agenda\nWe met today");
+}
+
+#[test]
+fn proposals_stay_strictly_before_the_caret() {
+    let text = "we went the the";
+    for caret in 0..=text.len() {
+        let result = ContextWorker::default().propose(text, caret, &Language::English);
+        for p in &result.proposals {
+            assert!(p.edit.range.end < caret, "{:?} at caret {}", p, caret);
+        }
+    }
+    // The word at the caret is still being typed
+    let result = ContextWorker::default().propose("hello", 5, &Language::English);
+    assert!(result.proposals.is_empty());
+}
+
+#[test]
+fn delegates_to_lm_backend() {
+    let mut worker = ContextWorker::default().with_lm(Box::new(|span: &str, _: &Language| {
+        Some(Correction { text: span.replace("recieved", "received"), confidence: 0.85 })
+    }));
+    let text = "We recieved it yesterday";
+    let result = worker.propose(text, text.len(), &Language::English);
+    let lm: Vec<_> = result.proposals.iter().filter(|p| p.kind == RepairKind::Lm).collect();
+    assert!(lm.iter().all(|p| p.confidence == 0.85));
+    let edits: Vec<TextEdit> = lm.iter().map(|p| p.edit.clone()).collect();
+    assert_eq!(TextEdit::apply_all(text, &edits).unwrap(), "We received it yesterday");
+}
@@ -1,13 +1,287 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use core_rs::diff::{
+    CaretSafeDiff, CaretSafeResult, ClipReason, ClippedEdit, DiffAlgorithm, DiffCalculator, DiffOp, DiffOptions, Granularity,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Tiny deterministic generator so property runs are reproducible without extra deps.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+// Mix of ASCII, combining marks, ZWJ emoji, flags, CJK and RTL.
+const ATOMS: &[&str] = &[
+    "a", "b", "e", "t", "h", " ", ".", "\n",
+    "e\u{301}", "\u{301}", "n\u{303}\u{323}",
+    "👩\u{200d}👩\u{200d}👧", "👍🏽", "\u{200d}", "🇩🇪",
+    "漢", "字", "ש", "ל", "ם",
+];
+
+fn random_text(rng: &mut XorShift, max_atoms: usize) -> String {
+    let len = rng.below(max_atoms + 1);
+    (0..len).map(|_| ATOMS[rng.below(ATOMS.len())]).collect()
+}
+
+fn mutate(rng: &mut XorShift, base: &str) -> String {
+    let mut atoms: Vec<String> = base.graphemes(true).map(str::to_string).collect();
+    for _ in 0..rng.below(4) {
+        match rng.below(3) {
+            0 if !atoms.is_empty() => {
+                let i = rng.below(atoms.len());
+                atoms.remove(i);
+            }
+            1 => {
+                let i = rng.below(atoms.len() + 1);
+                atoms.insert(i, ATOMS[rng.below(ATOMS.len())].to_string());
+            }
+            _ if !atoms.is_empty() => {
+                let i = rng.below(atoms.len());
+                atoms[i] = ATOMS[rng.below(ATOMS.len())].to_string();
+            }
+            _ => {}
+        }
+    }
+    atoms.concat()
+}
+
+fn all_options() -> Vec<DiffOptions> {
+    let mut out = Vec::new();
+    for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Patience, DiffAlgorithm::Histogram] {
+        for granularity in [Granularity::Char, Granularity::Grapheme] {
+            out.push(DiffOptions { algorithm, granularity });
+        }
+    }
+    out
+}
+
+fn edit_cost(ops: &[DiffOp], granularity: Granularity) -> usize {
+    let count = |s: &str| match granularity {
+        Granularity::Char => s.chars().count(),
+        Granularity::Grapheme => s.graphemes(true).count(),
+    };
+    ops.iter()
+        .filter(|op| op.is_edit())
+        .map(|op| count(op.original_text()) + count(op.modified_text()))
+        .sum()
+}
+
+fn lcs_len(a: &[&str], b: &[&str]) -> usize {
+    let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            dp[i + 1][j + 1] = if a[i] == b[j] { dp[i][j] + 1 } else { dp[i][j + 1].max(dp[i + 1][j]) };
+        }
+    }
+    dp[a.len()][b.len()]
+}
+
+#[test]
+fn round_trips_random_utf8() {
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..500 {
+        let a = random_text(&mut rng, 24);
+        let b = if rng.below(2) == 0 { mutate(&mut rng, &a) } else { random_text(&mut rng, 24) };
+        for options in all_options() {
+            let ops = DiffCalculator::calculate_diff_with(&a, &b, &options);
+            assert_eq!(DiffCalculator::try_apply_diff(&a, &ops).as_deref(), Ok(b.as_str()), "{:?} {:?} -> {:?}", options, a, b);
+            let original: String = ops.iter().map(DiffOp::original_text).collect();
+            assert_eq!(original, a);
+        }
+    }
+}
+
+#[test]
+fn myers_is_minimal() {
+    let mut rng = XorShift(42);
+    for _ in 0..300 {
+        let a = random_text(&mut rng, 12);
+        let b = mutate(&mut rng, &a);
+        let ga: Vec<&str> = a.graphemes(true).collect();
+        let gb: Vec<&str> = b.graphemes(true).collect();
+        let ops = DiffCalculator::calculate_diff(&a, &b);
+        assert_eq!(edit_cost(&ops, Granularity::Grapheme), ga.len() + gb.len() - 2 * lcs_len(&ga, &gb), "{:?} -> {:?}", a, b);
+    }
+}
+
+#[test]
+fn grapheme_ops_never_split_clusters() {
+    let mut rng = XorShift(7);
+    for _ in 0..300 {
+        let a = random_text(&mut rng, 16);
+        let b = mutate(&mut rng, &a);
+        for op in DiffCalculator::calculate_diff(&a, &b) {
+            for part in [op.original_text(), op.modified_text()] {
+                let rejoined: String = part.graphemes(true).collect();
+                assert_eq!(rejoined, part);
+            }
+        }
+        // Op boundaries in the original are grapheme boundaries
+        let boundaries: Vec<usize> = a.grapheme_indices(true).map(|(i, _)| i).chain([a.len()]).collect();
+        let mut offset = 0;
+        for op in DiffCalculator::calculate_diff(&a, &b) {
+            assert!(boundaries.contains(&offset));
+            offset += op.original_text().len();
+        }
+    }
+}
+
+#[test]
+fn runs_alternate_keep_and_change() {
+    let ops = DiffCalculator::calculate_diff("I saw teh cat.", "I saw the cat!");
+    assert_eq!(ops.first(), Some(&DiffOp::Keep("I saw t".into())));
+    assert_eq!(ops.last(), Some(&DiffOp::Replace(".".into(), "!".into())));
+    for pair in ops.windows(2) {
+        assert!(pair[0].is_edit() != pair[1].is_edit(), "adjacent runs must alternate: {:?}", ops);
+    }
+}
+
+#[test]
+fn identical_and_empty_inputs() {
+    assert_eq!(DiffCalculator::calculate_diff("same", "same"), vec![DiffOp::Keep("same".into())]);
+    assert!(DiffCalculator::calculate_diff("", "").is_empty());
+    assert_eq!(DiffCalculator::calculate_diff("", "new"), vec![DiffOp::Insert("new".into())]);
+    assert_eq!(DiffCalculator::calculate_diff("old", ""), vec![DiffOp::Delete("old".into())]);
+}
+
+#[test]
+fn zwj_sequence_replaced_as_a_unit() {
+    let ops = DiffCalculator::calculate_diff("hi 👩\u{200d}👩\u{200d}👧", "hi 👩\u{200d}👩\u{200d}👦");
+    assert_eq!(
+        ops,
+        vec![
+            DiffOp::Keep("hi ".into()),
+            DiffOp::Replace("👩\u{200d}👩\u{200d}👧".into(), "👩\u{200d}👩\u{200d}👦".into()),
+        ]
+    );
+    let chars = DiffOptions { granularity: Granularity::Char, ..Default::default() };
+    let ops = DiffCalculator::calculate_diff_with("hi 👩\u{200d}👩\u{200d}👧", "hi 👩\u{200d}👩\u{200d}👦", &chars);
+    assert_eq!(ops.last(), Some(&DiffOp::Replace("👧".into(), "👦".into())));
+}
+
+#[test]
+fn patience_anchors_on_unique_tokens() {
+    let options = DiffOptions { algorithm: DiffAlgorithm::Patience, granularity: Granularity::Grapheme };
+    let ops = DiffCalculator::calculate_diff_with("xaby", "yabx", &options);
+    assert_eq!(DiffCalculator::apply_diff("xaby", &ops), "yabx");
+    assert!(ops.contains(&DiffOp::Keep("ab".into())));
+}
+
+#[test]
+fn histogram_survives_deep_anchor_chains() {
+    // Every anchor matches one token and leaves the rest to the right-hand recursion
+    let a: String = (0..3000).filter_map(|i| char::from_u32(0x4E00 + i)).collect();
+    let b: String = a.chars().flat_map(|c| [c, '-']).collect();
+    let options = DiffOptions { algorithm: DiffAlgorithm::Histogram, granularity: Granularity::Grapheme };
+    let ops = DiffCalculator::calculate_diff_with(&a, &b, &options);
+    assert_eq!(DiffCalculator::try_apply_diff(&a, &ops).as_deref(), Ok(b.as_str()));
+}
+
+#[test]
+fn mismatched_ops_are_rejected() {
+    let ops = vec![DiffOp::Delete("xyz".into())];
+    assert!(DiffCalculator::try_apply_diff("abc", &ops).is_err());
+    assert_eq!(DiffCalculator::apply_diff("abc", &ops), "abc");
+}
+
+#[test]
+fn caret_safe_keeps_edits_before_caret() {
+    // caret sits after "teh " — the typo fix is allowed, the tail rewrite is not
+    let original = "I saw teh cat";
+    let result = CaretSafeDiff::new(10).calculate(original, "I saw the dog");
+    assert_eq!(DiffCalculator::apply_diff(original, &result.operations), "I saw the cat");
+    assert!(result.was_clipped());
+    assert!(result.clipped.iter().all(|c| c.offset >= 10 && c.reason == ClipReason::AtOrAfterCaret));
+}
+
+#[test]
+fn caret_safe_splits_straddling_delete() {
+    let ops = vec![DiffOp::Keep("ab".into()), DiffOp::Delete("cdef".into())];
+    let result = CaretSafeDiff::new(4).enforce("abcdef", &ops);
+    assert_eq!(
+        result.operations,
+        vec![DiffOp::Keep("ab".into()), DiffOp::Delete("cd".into()), DiffOp::Keep("ef".into())]
+    );
+    assert_eq!(
+        result.clipped,
+        vec![ClippedEdit { offset: 4, op: DiffOp::Delete("ef".into()), reason: ClipReason::AtOrAfterCaret }]
+    );
+}
+
+#[test]
+fn caret_safe_splits_straddling_replace_and_drops_insert_at_caret() {
+    let ops = vec![DiffOp::Keep("ab".into()), DiffOp::Replace("cd".into(), "XY".into()), DiffOp::Insert("!".into())];
+    let result = CaretSafeDiff::new(3).enforce("abcd", &ops);
+    assert_eq!(
+        result.operations,
+        vec![DiffOp::Keep("ab".into()), DiffOp::Replace("c".into(), "X".into()), DiffOp::Keep("d".into())]
+    );
+    assert_eq!(
+        result.clipped,
+        vec![
+            ClippedEdit { offset: 3, op: DiffOp::Replace("d".into(), "Y".into()), reason: ClipReason::StraddlesCaret },
+            ClippedEdit { offset: 4, op: DiffOp::Insert("!".into()), reason: ClipReason::AtOrAfterCaret },
+        ]
+    );
+
+    // An unchanged tail means the whole change lies before the caret
+    let result = CaretSafeDiff::new(3).enforce("abcd", &[DiffOp::Keep("ab".into()), DiffOp::Replace("cd".into(), "XYZd".into())]);
+    assert_eq!(DiffCalculator::apply_diff("abcd", &result.operations), "abXYZd");
+    assert!(!result.was_clipped());
+    // A shorter replacement leaves a deletion before the caret
+    let result = CaretSafeDiff::new(4).enforce("abcdef", &[DiffOp::Keep("ab".into()), DiffOp::Replace("cdef".into(), "".into())]);
+    assert_eq!(DiffCalculator::apply_diff("abcdef", &result.operations), "abef");
+    let result = CaretSafeDiff::new(4).enforce("abcdef", &[DiffOp::Keep("ab".into()), DiffOp::Replace("cdef".into(), "Z".into())]);
+    assert_eq!(result.operations[1..], [DiffOp::Replace("cd".into(), "Z".into()), DiffOp::Keep("ef".into())]);
+    assert_eq!(result.clipped, [ClippedEdit { offset: 4, op: DiffOp::Delete("ef".into()), reason: ClipReason::StraddlesCaret }]);
+
+    let ops = vec![DiffOp::Keep("ab".into()), DiffOp::Insert("!".into())];
+    assert!(CaretSafeDiff::new(2).enforce("ab", &ops).was_clipped());
+    assert!(!CaretSafeDiff::new(2).enforce("ab", &[DiffOp::Replace("ab".into(), "AB".into())]).was_clipped());
+}
+
+#[test]
+fn caret_safe_rejects_ops_that_do_not_match_original() {
+    // Too short for the text: nothing to enforce
+    let result = CaretSafeDiff::new(2).enforce("abcdef", &[DiffOp::Delete("abc".into())]);
+    assert_eq!(result, CaretSafeResult::default());
+    // Right length but the caret lands inside "é": the op is clipped whole
+    let result = CaretSafeDiff::new(1).enforce("abc", &[DiffOp::Delete("éx".into())]);
+    assert_eq!(result.operations, [DiffOp::Keep("éx".into())]);
+    assert_eq!(result.clipped, [ClippedEdit { offset: 0, op: DiffOp::Delete("éx".into()), reason: ClipReason::AtOrAfterCaret }]);
+    let result = CaretSafeDiff::new(1).enforce("ab", &[DiffOp::Replace("é".into(), "e".into())]);
+    assert!(result.was_clipped());
+}
+
+#[test]
+fn caret_safe_never_touches_text_after_caret() {
+    let mut rng = XorShift(0xC0FFEE);
+    for _ in 0..500 {
+        let a = random_text(&mut rng, 20);
+        let b = if rng.below(2) == 0 { mutate(&mut rng, &a) } else { random_text(&mut rng, 20) };
+        let caret = rng.below(a.len() + 1);
+        let result = CaretSafeDiff::new(caret).calculate(&a, &b);
+        let applied = DiffCalculator::try_apply_diff(&a, &result.operations).expect("ops must apply");
+
+        // The caret is snapped down to a grapheme boundary; everything from there is untouched
+        let snapped = a.grapheme_indices(true).map(|(i, _)| i).chain([a.len()]).rfind(|&i| i <= caret).unwrap();
+        assert!(applied.ends_with(&a[snapped..]), "{:?} -> {:?} @ {}: {:?}", a, b, caret, applied);
+        let mut offset = 0;
+        for op in &result.operations {
+            if op.is_edit() {
+                let end = offset + op.original_text().len();
+                assert!(end <= snapped && (offset < snapped || !op.original_text().is_empty()));
+            }
+            offset += op.original_text().len();
+        }
+    }
+}
@@ -1,13 +1,100 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+mod common;
+
+use std::sync::Arc;
+
+use core_rs::caret_monitor::EventKind;
+use core_rs::clock::{Clock, ManualClock};
+use core_rs::confidence::EditType;
+use core_rs::diff::TextEdit;
+use core_rs::engine::{Correction, Engine, EngineConfig, SkipReason};
+use core_rs::language_detection::Language;
+
+use common::typed;
+
+fn teh_fixer(region: &str, _language: &Language) -> Option<Correction> {
+    Some(Correction { text: region.replace("teh", "the"), confidence: 0.95 })
+}
+
+#[test]
+fn pause_produces_caret_safe_edits() {
+    let text = "I saw teh cat and teh dog";
+    let clock = ManualClock::new(1_000);
+    let mut engine = Engine::with_corrector(EngineConfig::default(), Box::new(teh_fixer));
+    engine.set_clock(Arc::new(clock.clone()));
+    engine.observe(typed(text.len(), clock.now_ms()));
+
+    // Still typing: nothing happens
+    clock.advance(100);
+    let out = engine.tick(text, text.len());
+    assert_eq!(out.diagnostics.skipped, Some(SkipReason::NotPaused));
+
+    clock.advance(300);
+    let out = engine.tick(text, text.len());
+    assert!(out.diagnostics.skipped.is_none(), "{:?}", out.diagnostics);
+    assert_eq!(TextEdit::apply_all(text, &out.edits).unwrap(), "I saw the cat and the dog");
+    assert!(out.edits.iter().all(|e| e.range.end <= text.len()));
+
+    // Nothing changed since: no duplicate work
+    clock.advance(100);
+    let again = engine.tick(text, text.len());
+    assert_eq!(again.diagnostics.skipped, Some(SkipReason::Unchanged));
+}
+
+#[test]
+fn edits_at_the_caret_are_clipped() {
+    // Caret sits right after the typo; the fix would touch the caret position
+    let text = "see teh";
+    let mut engine = Engine::with_corrector(
+        EngineConfig::default(),
+        Box::new(|region: &str, _: &Language| Some(Correction { text: format!("{}!", region), confidence: 1.0 })),
+    );
+    engine.observe(typed(text.len(), 0));
+    let out = engine.tick_at(text, text.len(), 500);
+    assert!(out.edits.is_empty());
+    assert_eq!(out.diagnostics.clipped.len(), 1);
+    assert_eq!(out.diagnostics.skipped, Some(SkipReason::Gated));
+}
+
+#[test]
+fn low_confidence_corrections_are_gated() {
+    let text = "teh end";
+    let mut engine = Engine::with_corrector(
+        EngineConfig::default(),
+        Box::new(|region: &str, _: &Language| Some(Correction { text: region.replace("teh", "the"), confidence: 0.5 })),
+    );
+    engine.observe(typed(text.len(), 0));
+    let out = engine.tick_at(text, text.len(), 500);
+    assert!(out.edits.is_empty());
+    assert!(out.diagnostics.gated > 0);
+}
+
+#[test]
+fn default_engine_proposes_nothing() {
+    let mut engine = Engine::new(EngineConfig::default());
+    engine.observe(typed(5, 0));
+    let out = engine.tick_at("hello", 5, 3_000);
+    assert_eq!(out.diagnostics.skipped, Some(SkipReason::NoCorrection));
+}
+
+#[test]
+fn undoing_an_edit_raises_the_bar_for_the_next_one() {
+    let text = "I saw teh cat";
+    let mut engine = Engine::with_corrector(
+        EngineConfig::default(),
+        Box::new(|region: &str, _: &Language| Some(Correction { text: region.replace("teh", "the"), confidence: 0.86 })),
+    );
+    engine.observe(typed(text.len(), 0));
+    let out = engine.tick_at(text, text.len(), 500);
+    assert!(!out.edits.is_empty());
+
+    // The user undoes the fix right away, then keeps typing
+    let mut undo = typed(text.len(), 1_000);
+    undo.kind = EventKind::Undo;
+    engine.observe(undo);
+    let text = "I saw teh cat sleep";
+    engine.observe(typed(text.len(), 1_200));
+    let out = engine.tick_at(text, text.len(), 1_700);
+    assert!(out.edits.is_empty());
+    assert_eq!(out.diagnostics.skipped, Some(SkipReason::Gated));
+    assert!(engine.adaptive().boost(EditType::Lm, &(6..9), 1_700) > 0.0);
+}
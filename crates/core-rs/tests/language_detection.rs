@@ -1,13 +1,120 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use core_rs::language_detection::{run_at, Language, LanguageDetector};
+
+#[test]
+fn detects_latin_languages_from_trigrams() {
+    let detector = LanguageDetector::new();
+    let cases = [
+        ("The dog sleeps in the garden while we wait for dinner", Language::English),
+        ("El perro duerme en el jardín mientras esperamos la cena", Language::Spanish),
+        ("Le chien dort dans le jardin pendant que nous attendons", Language::French),
+        ("Der Hund schläft im Garten, während wir auf das Essen warten", Language::German),
+        ("Il cane dorme nel giardino mentre aspettiamo la cena", Language::Italian),
+        ("O cão dorme no jardim enquanto esperamos o jantar", Language::Portuguese),
+        ("De hond slaapt in de tuin terwijl wij op het eten wachten", Language::Dutch),
+    ];
+    for (text, expected) in cases {
+        let (language, confidence) = detector.detect(text);
+        assert_eq!(language, expected, "{text}");
+        assert!(confidence >= 0.9, "{text}: {confidence}");
+    }
+}
+
+#[test]
+fn short_english_typing_clears_default_threshold() {
+    let detector = LanguageDetector::new();
+    for text in ["so i went home ", "i saw taht cat ", "we can't come as teh car broke. "] {
+        assert_eq!(detector.detect(text).0, Language::English, "{text}");
+    }
+}
+
+#[test]
+fn script_decides_short_non_latin_text() {
+    let detector = LanguageDetector::new();
+    for (text, expected) in [
+        ("東京", Language::Chinese),
+        ("こんにちは", Language::Japanese),
+        ("東京に行きます", Language::Japanese),
+        ("안녕하세요", Language::Korean),
+        ("привет", Language::Russian),
+    ] {
+        let (language, confidence) = detector.detect(text);
+        assert_eq!(language, expected, "{text}");
+        assert!(confidence > 0.9);
+    }
+}
+
+#[test]
+fn ambiguous_text_stays_unknown_below_threshold() {
+    let detector = LanguageDetector::new();
+    let (language, confidence) = detector.detect("taxi");
+    assert_eq!(language, Language::Unknown);
+    assert!(confidence < 0.8);
+
+    let mut strict = LanguageDetector::new();
+    strict.set_threshold(1.0);
+    assert_eq!(strict.detect("hello there").0, Language::Unknown);
+}
+
+#[test]
+fn no_letters_means_no_scores() {
+    let detector = LanguageDetector::new();
+    assert_eq!(detector.detect(""), (Language::Unknown, 0.0));
+    assert_eq!(detector.detect("12 + 7 = 19!"), (Language::Unknown, 0.0));
+    assert!(detector.scores("...").is_empty());
+}
+
+#[test]
+fn scores_form_a_distribution() {
+    let scores = LanguageDetector::new().scores("we will meet at the station tomorrow");
+    assert_eq!(scores[0].0, Language::English);
+    assert_eq!(scores.len(), 7);
+    let total: f32 = scores.iter().map(|(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-3);
+    assert!(scores.windows(2).all(|w| w[0].1 >= w[1].1));
+}
+
+#[test]
+fn codes_round_trip() {
+    for code in ["en", "es", "fr", "de", "it", "pt", "nl", "zh", "ja", "ko", "ru"] {
+        assert_eq!(Language::from_code(code).unwrap().code(), code);
+    }
+    assert_eq!(Language::from_code("en-GB"), Some(Language::English));
+    assert_eq!(Language::from_code("PT_br"), Some(Language::Portuguese));
+    assert_eq!(Language::from_code("xx"), None);
+}
+
+#[test]
+fn segments_code_switching_text_into_runs() {
+    let detector = LanguageDetector::new();
+    let text = "Dear team, please find attached the notes. Merci beaucoup pour votre aide avec ce projet. ";
+    let runs = detector.segment(text);
+    let languages: Vec<Language> = runs.iter().map(|r| r.language.clone()).collect();
+    assert_eq!(languages, [Language::English, Language::French]);
+    assert_eq!(&text[runs[1].range.clone()], "Merci beaucoup pour votre aide avec ce projet. ");
+    assert_eq!(runs[0].range.start, 0);
+    assert_eq!(runs[0].range.end, runs[1].range.start);
+    assert_eq!(runs[1].range.end, text.len());
+}
+
+#[test]
+fn short_loanword_clause_does_not_flip_run() {
+    let detector = LanguageDetector::new();
+    let text = "Wir treffen uns morgen im Büro, okay? Dann besprechen wir alles.";
+    let runs = detector.segment(text);
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].language, Language::German);
+    assert_eq!(runs[0].range, 0..text.len());
+}
+
+#[test]
+fn script_switches_run_regardless_of_length() {
+    let detector = LanguageDetector::new();
+    let text = "I will be there tomorrow morning. 東京に行きます。";
+    let runs = detector.segment(text);
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[1].language, Language::Japanese);
+    assert_eq!(run_at(&runs, text.len()).unwrap().language, Language::Japanese);
+    assert_eq!(run_at(&runs, 0).unwrap().language, Language::English);
+    assert!(detector.segment("").is_empty());
+    assert_eq!(detector.segment("ok")[0].language, Language::Unknown);
+}
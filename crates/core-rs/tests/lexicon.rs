@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use core_rs::language_detection::Language;
+use core_rs::lexicon::{self, edit_distance, Lexicon, Lexicons};
+use core_rs::typo_model::{KeyboardLayout, TypoModel};
+use core_rs::workers::noise::{KeyboardTypoRule, NoiseRegistry};
+
+#[test]
+fn english_lexicon_is_built_in() {
+    let english = lexicon::english();
+    assert_eq!(english.language, Language::English);
+    assert!(english.len() > 1000);
+    assert!(english.contains("the") && english.contains("World"));
+    assert!(!english.contains("wprld"));
+    assert!(english.prior("the") > english.prior("world"));
+    assert_eq!(english.prior("wprld"), 0.0);
+}
+
+#[test]
+fn lookup_finds_words_within_two_edits() {
+    let english = lexicon::english();
+    let near = english.lookup("wprld", 1);
+    assert!(near.iter().any(|s| s.word == "world" && s.distance == 1));
+    assert!(near.iter().all(|s| s.distance <= 1));
+
+    let far = english.lookup("wrlod", 2);
+    assert!(far.iter().any(|s| s.word == "world"));
+    assert!(far.windows(2).all(|w| w[0].distance <= w[1].distance));
+    // Requests beyond MAX_DISTANCE are capped
+    assert!(english.lookup("wprld", 5).iter().all(|s| s.distance <= lexicon::MAX_DISTANCE));
+    assert_eq!(english.lookup("The", 0)[0].word, "the");
+}
+
+#[test]
+fn word_lists_take_counts_or_ranks() {
+    let lexicon = Lexicon::from_word_list(Language::French, "# fr\nle 500\nde\nle 9\n\nmaison 20\n");
+    assert_eq!(lexicon.len(), 3);
+    assert_eq!(lexicon.frequency("le"), Some(500));
+    // "de" is second of the counted lines, so it gets the Zipf share of rank 2
+    assert_eq!(lexicon.frequency("de"), Some(500_000));
+    assert_eq!(lexicon.words().collect::<Vec<_>>(), ["le", "de", "maison"]);
+
+    let dir = std::env::temp_dir().join(format!("mindtype-lexicon-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("de.txt");
+    std::fs::write(&path, "haus\nwelt\n").unwrap();
+    let loaded = Lexicon::load_file(Language::German, &path).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(loaded.lookup("wlet", 1).iter().any(|s| s.word == "welt"));
+}
+
+#[test]
+fn embedded_english_matches_its_word_list() {
+    let list = include_str!("../data/lexicon/en.txt");
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/lexicon/en.lex");
+    let encoded = Lexicon::from_word_list(Language::English, list).to_bytes();
+    if std::env::var_os("MINDTYPE_WRITE_LEXICON").is_some() {
+        std::fs::write(path, &encoded).unwrap();
+    }
+    assert!(std::fs::read(path).unwrap() == encoded, "en.lex is stale; see the en.txt header");
+    // Smaller than the plain list even with every frequency spelled out
+    assert!(encoded.len() < list.len());
+}
+
+#[test]
+fn compact_encoding_round_trips() {
+    let lexicon = Lexicon::from_word_list(Language::French, "été 90\nêtre 80\ndonc\ndonné 7\nl'eau 3\n");
+    let decoded = Lexicon::from_bytes(Language::French, &lexicon.to_bytes()).unwrap();
+    assert_eq!(decoded.len(), 5);
+    for word in lexicon.words() {
+        assert_eq!(decoded.frequency(word), lexicon.frequency(word));
+    }
+    assert_eq!(decoded.words().next(), Some("donc"));
+    assert!(decoded.lookup("donne", 1).iter().any(|s| s.word == "donné"));
+
+    assert!(Lexicon::from_bytes(Language::French, b"plain text").is_err());
+    let truncated = lexicon.to_bytes();
+    assert!(Lexicon::from_bytes(Language::French, &truncated[..truncated.len() - 3]).is_err());
+}
+
+#[test]
+fn lexicons_register_per_language() {
+    let mut lexicons = Lexicons::builtin();
+    assert_eq!(lexicons.codes(), ["en"]);
+    assert!(lexicons.get(&Language::French).is_none());
+
+    let dir = std::env::temp_dir().join(format!("mindtype-lexicons-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("fr.lex");
+    std::fs::write(&path, Lexicon::from_word_list(Language::French, "maison\nmonde\n").to_bytes()).unwrap();
+    lexicons.load_file(Language::French, &path).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(lexicons.codes(), ["en", "fr"]);
+    assert!(lexicons.get(&Language::French).unwrap().contains("monde"));
+
+    let mut registry = NoiseRegistry::default();
+    let model = TypoModel::new(KeyboardLayout::Qwerty);
+    registry.register(Box::new(KeyboardTypoRule::with_lexicons(model, lexicons.clone())));
+    let text = "le mpnde est grand ";
+    assert_eq!(registry.sweep_for(text, text.len(), &Language::French).0, "le monde est grand ");
+    assert_eq!(registry.sweep_for(text, text.len(), &Language::Spanish).0, text);
+
+    // An Unknown lexicon serves languages without their own
+    lexicons.insert(Arc::new(Lexicon::from_word_list(Language::Unknown, "hola\n")));
+    assert!(lexicons.get(&Language::Spanish).unwrap().contains("hola"));
+    assert!(lexicons.remove(&Language::French).is_some());
+    assert!(!lexicons.get(&Language::French).unwrap().contains("monde"));
+}
+
+#[test]
+fn contractions_are_looked_up_whole() {
+    let mut registry = NoiseRegistry::empty();
+    let model = TypoModel::new(KeyboardLayout::Qwerty);
+    registry.register(Box::new(KeyboardTypoRule::with_lexicon(model, lexicon::english())));
+    let sweep = |text: &str| registry.sweep_for(text, text.len(), &Language::English).0;
+    assert_eq!(sweep("i don't know, it isn't here "), "i don't know, it isn't here ");
+    assert_eq!(sweep("it does'nt work "), "it doesn't work ");
+    assert_eq!(sweep("it does’nt work "), "it doesn’t work ");
+    // The contraction at the caret is still being typed
+    assert_eq!(sweep("it does'nt"), "it does'nt");
+}
+
+#[test]
+fn edit_distance_counts_transpositions_once() {
+    assert_eq!(edit_distance("teh", "the"), 1);
+    assert_eq!(edit_distance("wprld", "world"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn default_registry_leaves_words_outside_the_lexicon_alone() {
+    let registry = NoiseRegistry::default();
+    assert!(!registry.rule_names().contains(&"keyboard-typo"));
+    for word in ["bug", "nap", "vet", "nope", "nut", "gum", "rib"] {
+        assert!(!lexicon::english().contains(word));
+        let text = format!("the {word} is here ");
+        assert_eq!(registry.sweep_for(&text, text.len(), &Language::English).0, text);
+    }
+}
+
+#[test]
+fn opted_in_rule_corrects_english_typos_only() {
+    let mut registry = NoiseRegistry::default();
+    let model = TypoModel::new(KeyboardLayout::Qwerty);
+    registry.register(Box::new(KeyboardTypoRule::with_lexicon(model, lexicon::english())));
+    let (english, _) = registry.sweep_for("Hello wprld and ", 16, &Language::English);
+    assert_eq!(english, "Hello world and ");
+    let (german, _) = registry.sweep_for("Hallo wprld und ", 16, &Language::German);
+    assert_eq!(german, "Hallo wprld und ");
+    let (clean, edits) = registry.sweep_for("The house is on the hill. ", 26, &Language::English);
+    assert_eq!(clean, "The house is on the hill. ");
+    assert!(edits.is_empty());
+}
+
+#[test]
+fn close_runners_up_block_the_correction() {
+    // "f" neighbours both "t" and "r", so "caf" has no clear winner
+    let model = TypoModel::new(KeyboardLayout::Qwerty);
+    let tied = KeyboardTypoRule::new(model, ["cat", "car"]);
+    assert_eq!(tied.best_candidate("caf", &Language::Unknown), None);
+    let clear = KeyboardTypoRule::new(model, ["cat", "dog"]);
+    assert_eq!(clear.best_candidate("caf", &Language::Unknown).map(|(word, _)| word), Some("cat"));
+}
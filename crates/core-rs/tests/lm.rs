@@ -10,4 +10,4 @@
 */
 
 #[test]
-fn stub_lm() { assert!(true); }
+fn stub_lm() {}
@@ -1,13 +1,144 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use core_rs::diff::{DiffCalculator, TextEdit};
+use core_rs::llm::StubStream;
+use core_rs::lm::merge::stream_merge;
+use core_rs::merge::{MergeConfig, Merger, StaleReason, TokenJoin};
+
+#[tokio::test]
+async fn streams_batches_against_original_band() {
+    let original = "i think teh cat sat on teh mat and wnet home";
+    let mut stream = StubStream::new("I think the cat sat on the mat and went home");
+    let mut merger = Merger::new(original);
+    let batches = stream_merge(&mut stream, &mut merger).await;
+
+    assert!(batches.len() >= 2, "expected incremental batches, got {:?}", batches);
+    assert!(batches.last().unwrap().is_final);
+    for pair in batches.windows(2) {
+        assert!(pair[1].tokens_consumed - pair[0].tokens_consumed <= 4);
+        assert!(pair[1].sequence > pair[0].sequence);
+    }
+    // Every batch is relative to the original, and the untouched tail is kept
+    for batch in &batches {
+        assert!(DiffCalculator::try_apply_diff(original, &batch.operations).is_ok());
+    }
+    let first = DiffCalculator::apply_diff(original, &batches[0].operations);
+    assert!(first.ends_with("wnet home"), "{}", first);
+    let last = DiffCalculator::apply_diff(original, &batches.last().unwrap().operations);
+    assert_eq!(last, "I think the cat sat on the mat and went home");
+    assert_eq!(merger.get_result(), "I think the cat sat on the mat and went home");
+}
+
+#[tokio::test]
+async fn flushes_on_punctuation_boundaries() {
+    let mut stream = StubStream::new("Yes, fine.");
+    let mut merger = Merger::new("yes fine");
+    let batches = stream_merge(&mut stream, &mut merger).await;
+    assert_eq!(batches[0].tokens_consumed, 1, "comma should flush immediately");
+    assert_eq!(merger.get_result(), "Yes, fine.");
+}
+
+#[test]
+fn anchored_with_keeps_the_config() {
+    let config = MergeConfig { join: TokenJoin::Concat, ..Default::default() };
+    let mut merger = Merger::anchored_with("helo wrld", 6, 3, config);
+    for token in ["hel", "lo", " wor", "ld"] {
+        merger.apply_token(&token.to_string());
+    }
+    merger.finish();
+    assert_eq!(merger.get_result(), "hello world");
+    assert_eq!((merger.anchor().start, merger.anchor().revision), (6, 3));
+}
+
+#[test]
+fn concat_join_handles_subword_tokens() {
+    let config = MergeConfig { join: TokenJoin::Concat, ..Default::default() };
+    let mut merger = Merger::with_config("helo wrld", config);
+    for token in ["hel", "lo", " wor", "ld"] {
+        merger.apply_token(&token.to_string());
+    }
+    merger.finish();
+    assert_eq!(merger.get_result(), "hello world");
+}
+
+#[test]
+fn identical_batches_are_not_re_emitted() {
+    let config = MergeConfig { batch_tokens: 1, ..Default::default() };
+    let mut merger = Merger::with_config("all fine", config);
+    assert!(merger.apply_token(&"all".to_string()).is_some());
+    // Unchanged words align to the same no-op batch: nothing new to send
+    assert!(merger.apply_token(&"fine".to_string()).is_none());
+    assert!(merger.finish().unwrap().is_final);
+    assert!(Merger::new("anything").finish().is_none());
+}
+
+#[test]
+fn rebases_over_user_edits_before_the_band() {
+    let document = "Hi. i think teh cat sat";
+    let band_start = document.find("i think").unwrap();
+    let mut merger = Merger::anchored(&document[band_start..], band_start, 1);
+    for token in ["i", "think", "the", "cat", "sat"] {
+        merger.apply_token(&token.to_string());
+    }
+    let batch = merger.finish().unwrap();
+
+    // User inserts a word at the very start while the LM was streaming
+    let edit = TextEdit::new(0..0, "Oh. ");
+    merger.observe_user_edit(&edit, 2).unwrap();
+    let document = TextEdit::apply_all(document, &[edit]).unwrap();
+
+    let edits = merger.rebase(&batch, &document, 2).unwrap();
+    assert_eq!(TextEdit::apply_all(&document, &edits).unwrap(), "Oh. Hi. i think the cat sat");
+}
+
+#[test]
+fn typing_after_the_band_keeps_edits_valid() {
+    let mut merger = Merger::anchored("teh cat", 0, 7);
+    merger.apply_token(&"the".to_string());
+    merger.apply_token(&"cat".to_string());
+    let batch = merger.finish().unwrap();
+    merger.observe_user_edit(&TextEdit::new(7..7, " sat"), 8).unwrap();
+    let edits = merger.rebase(&batch, "teh cat sat", 8).unwrap();
+    assert_eq!(TextEdit::apply_all("teh cat sat", &edits).unwrap(), "the cat sat");
+}
+
+#[test]
+fn discards_edits_when_the_band_changes() {
+    let mut merger = Merger::anchored("teh cat", 4, 1);
+    merger.apply_token(&"the".to_string());
+    assert_eq!(merger.observe_user_edit(&TextEdit::new(5..6, "X"), 2), Err(StaleReason::BandEdited));
+    assert_eq!(merger.stale_reason().map(|r| r.code()), Some("BAND_EDITED"));
+    assert!(merger.apply_token(&"cat".to_string()).is_none());
+    assert!(merger.finish().is_none());
+}
+
+#[test]
+fn detects_unreported_changes() {
+    let mut merger = Merger::anchored("teh", 0, 1);
+    merger.apply_token(&"the".to_string());
+    let batch = merger.finish().unwrap();
+    assert_eq!(
+        merger.rebase(&batch, "teh", 3),
+        Err(StaleReason::RevisionMismatch { expected: 1, actual: 3 })
+    );
+
+    let mut merger = Merger::anchored("teh", 0, 1);
+    merger.apply_token(&"the".to_string());
+    let batch = merger.finish().unwrap();
+    assert_eq!(merger.rebase(&batch, "ten", 1), Err(StaleReason::FingerprintMismatch));
+}
+
+#[test]
+fn long_bands_stay_aligned_as_tokens_stream() {
+    let original = format!("{}wnet home.", "teh quick brown fox jumps over the lazy dog and ".repeat(12));
+    let corrected = original.replace("teh", "the").replace("wnet", "went").replace(" lazy dog", " dog");
+    let config = MergeConfig { batch_tokens: 1, ..Default::default() };
+    let mut merger = Merger::with_config(&original, config);
+    let words: Vec<&str> = corrected.split(' ').collect();
+    for (i, word) in words.iter().enumerate() {
+        merger.apply_token(&word.to_string());
+        // The streamed words are merged in and the rest of the band is untouched
+        let streamed = words[..=i].join(" ");
+        assert!(merger.get_result().starts_with(&streamed), "token {i}: {}", merger.get_result());
+    }
+    merger.finish();
+    assert_eq!(merger.get_result(), corrected);
+}
@@ -10,4 +10,4 @@
 */
 
 #[test]
-fn stub_${f%.rs}() { assert!(true); }
+fn stub_lm_stream() {}
@@ -0,0 +1,202 @@
+use core_rs::caret_monitor::{CaretEvent, EventKind, FieldKind, InputModality, SelectionFacet};
+use core_rs::diff::TextEdit;
+use core_rs::engine::{Engine, EngineConfig};
+use core_rs::language_detection::Language;
+use core_rs::workers::noise::{NoiseInput, NoiseProposal, NoiseRegistry, NoiseRule};
+use core_rs::workers::rule_packs::{RulePack, RulePacks};
+
+fn fix(text: &str) -> Option<String> {
+    fix_in(text, Language::English)
+}
+
+fn fix_in(text: &str, language: Language) -> Option<String> {
+    let proposal = NoiseRegistry::new().transform(&NoiseInput::new(text, text.len()).with_language(language))?;
+    TextEdit::apply_all(text, &[proposal.edit])
+}
+
+#[test]
+fn rules_run_in_priority_order() {
+    assert_eq!(
+        NoiseRegistry::new().rule_names(),
+        ["whitespace-normalization", "punctuation-normalization"]
+    );
+}
+
+#[test]
+fn each_builtin_rule_fires() {
+    assert_eq!(fix("I saw taht one ").as_deref(), Some("I saw that one "));
+    assert_eq!(fix("Recieve it ").as_deref(), Some("Receive it "));
+    assert_eq!(fix("too  many spaces ").as_deref(), Some("too many spaces "));
+    assert_eq!(fix("trailing  \nnext ").as_deref(), Some("trailing\nnext "));
+    assert_eq!(fix("the brwon fox").as_deref(), Some("the brown fox"));
+    assert_eq!(fix("red ,green").as_deref(), Some("red,green"));
+    assert_eq!(fix("red,green ").as_deref(), Some("red, green "));
+    assert_eq!(fix("1,000 and 3 .5 ok "), None);
+}
+
+#[test]
+fn never_touches_the_word_at_the_caret() {
+    // "taht" is still being typed
+    assert_eq!(fix("so taht"), None);
+    // Double space right before the caret is left alone
+    assert_eq!(fix("word  "), None);
+    // Text after the caret is out of reach
+    let text = "fine here taht";
+    let input = NoiseInput::new(text, 9);
+    assert_eq!(NoiseRegistry::new().transform(&input), None);
+}
+
+#[test]
+fn edits_stay_inside_the_window_and_hint() {
+    let text = format!("taht {}", "ok ".repeat(40));
+    assert_eq!(fix(&text), None, "typo is more than 80 bytes behind the caret");
+
+    let text = "taht one taht two ";
+    let mut input = NoiseInput::new(text, text.len()).with_language(Language::English);
+    input.hint = Some(0..5);
+    let proposal = NoiseRegistry::new().transform(&input).unwrap();
+    assert_eq!(proposal.edit.range, 0..4);
+}
+
+#[test]
+fn rules_can_be_disabled() {
+    let mut registry = NoiseRegistry::new();
+    assert!(registry.set_enabled("whitespace-normalization", false));
+    assert!(!registry.set_enabled("no-such-rule", false));
+    assert!(!registry.is_enabled("whitespace-normalization"));
+    assert_eq!(registry.transform(&NoiseInput::new("too  many ", 10)), None);
+}
+
+struct Shout;
+
+impl NoiseRule for Shout {
+    fn name(&self) -> &'static str {
+        "shout"
+    }
+
+    fn priority(&self) -> i32 {
+        -1
+    }
+
+    fn apply(&self, input: &NoiseInput) -> Option<NoiseProposal> {
+        let at = input.text.find("hey")?;
+        Some(NoiseProposal { rule: self.name(), edit: TextEdit::new(at..at + 3, "HEY"), confidence: 0.9 })
+    }
+}
+
+#[test]
+fn custom_rules_are_ordered_by_priority() {
+    let mut registry = NoiseRegistry::new();
+    registry.register(Box::new(Shout));
+    assert_eq!(registry.rule_names()[0], "shout");
+    let proposal = registry.transform(&NoiseInput::new("hey taht ", 9)).unwrap();
+    assert_eq!(proposal.rule, "shout");
+
+    // A proposal reaching past the caret is refused even from a custom rule
+    let mut only = NoiseRegistry::empty();
+    only.register(Box::new(Shout));
+    assert_eq!(only.transform(&NoiseInput::new("ok hey", 5)), None);
+}
+
+#[test]
+fn sweep_fixes_everything_behind_the_caret() {
+    let (text, edits) = NoiseRegistry::new().sweep_for("I dont know waht  teh plan is", 25, &Language::English);
+    assert_eq!(text, "I don't know what the plan is");
+    assert_eq!(edits.len(), 4);
+}
+
+#[test]
+fn valid_english_words_are_left_alone() {
+    let registry = NoiseRegistry::new();
+    for text in [
+        "The new platform works ",
+        "I have a uniform here ",
+        "fill the form in ",
+        "The dog wagged its tail ",
+        "a manger scene ",
+        "A banana n ice thing ",
+        "I cant say, it wont last ",
+    ] {
+        assert_eq!(registry.sweep_for(text, text.len(), &Language::English).0, text);
+    }
+}
+
+#[test]
+fn substitutions_match_whole_words_only() {
+    let sweep = |text: &str| NoiseRegistry::new().sweep_for(text, text.len(), &Language::English).0;
+    assert_eq!(sweep("the ofice is open "), "the office is open ");
+    assert_eq!(sweep("the bigofice is open "), "the bigofice is open ");
+    assert_eq!(sweep("a trk passed "), "a truck passed ");
+    assert_eq!(sweep("a fastrk passed "), "a fastrk passed ");
+    // Still being typed: "hgeard" may grow into another word
+    assert_eq!(sweep("I hgeard"), "I hgeard");
+}
+
+#[test]
+fn registry_works_as_engine_corrector() {
+    let text = "I saw taht cat yesterday";
+    let mut engine = Engine::with_corrector(EngineConfig::default(), Box::new(NoiseRegistry::new()));
+    engine.observe(CaretEvent {
+        kind: EventKind::Input,
+        timestamp_ms: 0,
+        caret: text.len() as u32,
+        text_len: text.len() as u32,
+        selection: SelectionFacet::default(),
+        input_modality: InputModality::Keyboard,
+        field_kind: FieldKind::TextArea,
+        ime_active: false,
+        blocked: false,
+        input_type: Some("insertText".into()),
+    });
+    let out = engine.tick_at(text, text.len(), 500);
+    assert_eq!(TextEdit::apply_all(text, &out.edits).as_deref(), Some("I saw that cat yesterday"));
+}
+
+#[test]
+fn builtin_packs_fix_language_specific_noise() {
+    assert_eq!(RulePacks::builtin().codes(), ["de", "en", "es", "fr"]);
+    assert_eq!(fix_in("well im sure theyre here ", Language::English).as_deref(), Some("well im sure they're here "));
+    assert_eq!(fix_in("Youre right ", Language::English).as_deref(), Some("You're right "));
+    assert_eq!(fix_in("Tu viens? Oui ", Language::French).as_deref(), Some("Tu viens\u{a0}? Oui "));
+    assert_eq!(fix_in("Attention : ici ", Language::French).as_deref(), Some("Attention\u{a0}: ici "));
+    assert_eq!(fix_in("Il est 10:30 ", Language::French), None);
+    assert_eq!(fix_in("der hund schläft ", Language::German).as_deref(), Some("der Hund schläft "));
+    assert_eq!(fix_in("Como estas? bien ", Language::Spanish).as_deref(), Some("¿Como estas? bien "));
+    assert_eq!(fix_in("Hola. Que bien! ", Language::Spanish).as_deref(), Some("Hola. ¡Que bien! "));
+    assert_eq!(fix_in("Hola, ¿qué tal? ", Language::Spanish), None);
+}
+
+#[test]
+fn packs_only_run_for_their_language() {
+    assert_eq!(fix_in("der hund schläft ", Language::Unknown), None);
+    // English fixes are a pack too, so unknown text only gets spacing and punctuation
+    assert_eq!(fix_in("I saw taht one ", Language::Unknown), None);
+    assert_eq!(fix_in("too  many ", Language::Unknown).as_deref(), Some("too many "));
+    assert_eq!(fix_in("No sé si vienes mañana ", Language::Spanish), None);
+    assert_eq!(fix_in("la personne dont je parle ", Language::French), None);
+    assert_eq!(fix_in("der hund schläft ", Language::French), None);
+    assert_eq!(fix_in("Tu viens? Oui ", Language::English), None);
+}
+
+#[test]
+fn packs_load_from_data_files() {
+    let dir = std::env::temp_dir().join(format!("mindtype-packs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("it.json"), r#"{"language": "IT", "words": {"perche": "perché"}}"#).unwrap();
+    std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+    let mut registry = NoiseRegistry::new();
+    assert_eq!(registry.packs.load_dir(&dir).unwrap(), ["it"]);
+    let input = NoiseInput::new("Perche no ", 10).with_language(Language::Italian);
+    assert_eq!(registry.transform(&input).unwrap().edit, TextEdit::new(0..6, "Perché"));
+
+    std::fs::write(dir.join("bad.json"), "{").unwrap();
+    assert!(registry.packs.load_dir(&dir).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // Replacing a built-in pack
+    registry.packs.insert(RulePack { language: "de".into(), ..RulePack::default() });
+    assert!(registry.packs.get(&Language::German).unwrap().nouns.is_empty());
+    let (text, _) = registry.sweep_for("der hund schläft ", 17, &Language::German);
+    assert_eq!(text, "der hund schläft ");
+}
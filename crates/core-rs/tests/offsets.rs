@@ -0,0 +1,63 @@
+use core_rs::diff::{CaretSafeDiff, TextEdit};
+use core_rs::offsets::{self, OffsetUnit};
+
+// "é" is precomposed (2 bytes, 1 UTF-16), "👍🏽" is two astral scalars (8 bytes, 4 UTF-16),
+// "e\u{301}" is one grapheme of two scalars.
+const TEXT: &str = "é👍🏽 e\u{301}!";
+
+#[test]
+fn converts_between_all_units() {
+    let byte_after_emoji = "é👍🏽".len();
+    assert_eq!(offsets::from_byte(TEXT, byte_after_emoji, OffsetUnit::Utf16), Some(5));
+    assert_eq!(offsets::from_byte(TEXT, byte_after_emoji, OffsetUnit::Scalar), Some(3));
+    assert_eq!(offsets::from_byte(TEXT, byte_after_emoji, OffsetUnit::Grapheme), Some(2));
+
+    for unit in [OffsetUnit::Utf16, OffsetUnit::Scalar, OffsetUnit::Grapheme] {
+        let n = offsets::len_in(TEXT, unit);
+        assert_eq!(offsets::to_byte(TEXT, n, unit), Some(TEXT.len()));
+        assert_eq!(offsets::to_byte(TEXT, n + 1, unit), None);
+        assert_eq!(offsets::convert(TEXT, n, unit, OffsetUnit::Utf8), Some(TEXT.len()));
+    }
+}
+
+#[test]
+fn rejects_offsets_inside_units() {
+    // Inside the surrogate pair of 👍
+    assert_eq!(offsets::utf16_to_byte(TEXT, 2), None);
+    // Inside the UTF-8 encoding of é
+    assert_eq!(offsets::from_byte(TEXT, 1, OffsetUnit::Utf16), None);
+    // Between "e" and its combining accent: a scalar boundary but not a grapheme boundary
+    let combining = TEXT.find('\u{301}').unwrap();
+    assert!(offsets::from_byte(TEXT, combining, OffsetUnit::Scalar).is_some());
+    assert_eq!(offsets::from_byte(TEXT, combining, OffsetUnit::Grapheme), None);
+}
+
+#[test]
+fn clamped_conversion_snaps_back() {
+    assert_eq!(offsets::utf16_to_byte_clamped(TEXT, 2), "é".len());
+    assert_eq!(offsets::utf16_to_byte_clamped(TEXT, 999), TEXT.len());
+}
+
+#[test]
+fn text_edits_round_trip_through_utf16() {
+    let original = "😀 teh cat";
+    let result = CaretSafeDiff::new(original.len()).calculate(original, "😀 the cat");
+    let edits = result.edits();
+    assert_eq!(TextEdit::apply_all(original, &edits).as_deref(), Some("😀 the cat"));
+
+    let utf16: Vec<TextEdit> = edits.iter().map(|e| e.to_unit(original, OffsetUnit::Utf16).unwrap()).collect();
+    assert!(utf16.iter().all(|e| e.range.start >= 3), "emoji counts as two UTF-16 units plus the space");
+    let back: Vec<TextEdit> = utf16
+        .iter()
+        .map(|e| TextEdit::from_unit(original, e.range.clone(), OffsetUnit::Utf16, e.replacement.clone()).unwrap())
+        .collect();
+    assert_eq!(back, edits);
+}
+
+#[test]
+fn overlapping_edits_are_rejected() {
+    let edits = [TextEdit::new(0..3, "x"), TextEdit::new(2..4, "y")];
+    assert_eq!(TextEdit::apply_all("abcdef", &edits), None);
+    let edits = [TextEdit::new(4..6, "Z"), TextEdit::new(0..1, "A")];
+    assert_eq!(TextEdit::apply_all("abcdef", &edits).as_deref(), Some("AbcdZ"));
+}
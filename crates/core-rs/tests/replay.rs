@@ -0,0 +1,128 @@
+mod common;
+
+use std::sync::Arc;
+
+use core_rs::caret_monitor::Thresholds;
+use core_rs::clock::ManualClock;
+use core_rs::diff::TextEdit;
+use core_rs::engine::{Correction, Engine, EngineConfig, SkipReason};
+use core_rs::language_detection::Language;
+use core_rs::replay::{SessionRecorder, SessionReplayer, TraceRecord, TRACE_VERSION};
+
+use common::typed;
+
+fn teh_engine() -> Engine {
+    Engine::with_corrector(
+        EngineConfig::default(),
+        Box::new(|region: &str, _: &Language| Some(Correction { text: region.replace("teh", "the"), confidence: 0.95 })),
+    )
+}
+
+/// Types `text` one character every 50ms, ticking after each keystroke and once after a pause
+fn record_session(text: &str, with_text: bool) -> Vec<u8> {
+    let mut recorder = if with_text { SessionRecorder::with_text(Vec::new()) } else { SessionRecorder::new(Vec::new()) };
+    let mut t = 0;
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        let caret = text[..end].encode_utf16().count();
+        t += 50;
+        recorder.record_event(&typed(caret, t)).unwrap();
+        recorder.record_text(t, &text[..end], caret).unwrap();
+        recorder.record_tick(t + 10).unwrap();
+    }
+    recorder.record_tick(t + 600).unwrap();
+    recorder.into_inner()
+}
+
+#[test]
+fn trace_round_trips_through_jsonl() {
+    let trace = record_session("teh", true);
+    let replayer = SessionReplayer::from_reader(trace.as_slice()).unwrap();
+    let records = replayer.records();
+    assert!(matches!(records[0], TraceRecord::Header { version: TRACE_VERSION }));
+    assert_eq!(records.len(), 1 + 3 * 3 + 1);
+
+    let mut again = SessionRecorder::with_text(Vec::new());
+    for record in &records[1..] {
+        match record {
+            TraceRecord::Event { event } => again.record_event(event).unwrap(),
+            TraceRecord::Text { timestamp_ms, text, caret } => again.record_text(*timestamp_ms, text, *caret).unwrap(),
+            TraceRecord::Tick { timestamp_ms } => again.record_tick(*timestamp_ms).unwrap(),
+            TraceRecord::Header { .. } => unreachable!(),
+        }
+    }
+    assert_eq!(again.into_inner(), trace);
+}
+
+#[test]
+fn text_is_not_recorded_without_consent() {
+    let trace = String::from_utf8(record_session("secret", false)).unwrap();
+    assert!(!trace.contains("secret"));
+    assert!(!trace.contains("\"type\":\"text\""));
+}
+
+#[test]
+fn replay_through_engine_matches_golden() {
+    let trace = record_session("I saw teh cat.", true);
+    let replayer = SessionReplayer::from_reader(trace.as_slice()).unwrap();
+    let output = replayer.replay(&mut teh_engine());
+
+    // Ticks while typing are skipped; only the final pause produces edits
+    let (last, typing) = output.ticks.split_last().unwrap();
+    assert!(typing.iter().all(|t| t.edits.is_empty()));
+    assert!(typing.iter().any(|t| t.skipped == Some(SkipReason::NotPaused)));
+    assert_eq!(last.skipped, None);
+    assert_eq!(TextEdit::apply_all("I saw teh cat.", &last.edits).as_deref(), Some("I saw the cat."));
+    assert!(!output.snapshots.is_empty());
+
+    // Same trace, fresh engine: identical output, including serialized form
+    let again = replayer.replay(&mut teh_engine());
+    assert_eq!(again, output);
+    assert_eq!(serde_json::to_string(&again).unwrap(), serde_json::to_string(&output).unwrap());
+}
+
+#[test]
+fn replay_reads_utf16_carets_and_restores_the_clock() {
+    let text = "Café 👋 teh cat.";
+    let trace = record_session(text, true);
+    let replayer = SessionReplayer::from_reader(trace.as_slice()).unwrap();
+    let mut engine = teh_engine();
+    let clock = ManualClock::new(42);
+    engine.set_clock(Arc::new(clock.clone()));
+    let output = replayer.replay(&mut engine);
+    let last = output.ticks.last().unwrap();
+    assert_eq!(TextEdit::apply_all(text, &last.edits).as_deref(), Some("Café 👋 the cat."));
+
+    clock.set(1_000);
+    assert_eq!(engine.now_ms(), 1_000);
+
+    // Version 1 traces stored byte carets
+    let v1 = format!(
+        "{{\"type\":\"header\",\"version\":1}}\n{}\n{}\n{}\n",
+        serde_json::to_string(&TraceRecord::Event { event: typed(13, 4_400) }).unwrap(),
+        serde_json::to_string(&TraceRecord::Text { timestamp_ms: 0, text: "Café teh cat.".into(), caret: 14 }).unwrap(),
+        serde_json::to_string(&TraceRecord::Tick { timestamp_ms: 5_000 }).unwrap(),
+    );
+    let output = SessionReplayer::from_reader(v1.as_bytes()).unwrap().replay(&mut teh_engine());
+    assert_eq!(TextEdit::apply_all("Café teh cat.", &output.ticks[0].edits).as_deref(), Some("Café the cat."));
+}
+
+#[test]
+fn replay_monitor_is_deterministic() {
+    let trace = record_session("hello world", false);
+    let replayer = SessionReplayer::from_reader(trace.as_slice()).unwrap();
+    let first = replayer.replay_monitor(Thresholds::default());
+    assert!(!first.is_empty());
+    assert_eq!(first, replayer.replay_monitor(Thresholds::default()));
+}
+
+#[test]
+fn malformed_lines_report_their_position() {
+    let trace = "{\"type\":\"header\",\"version\":1}\n\n{\"type\":\"tick\"}\n";
+    let err = SessionReplayer::from_reader(trace.as_bytes()).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("line 3"), "{}", err);
+
+    let future = "{\"type\":\"header\",\"version\":99}\n";
+    assert!(SessionReplayer::from_reader(future.as_bytes()).is_err());
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use core_rs::clock::ManualClock;
+use core_rs::diff::TextEdit;
+use core_rs::language_detection::Language;
+use core_rs::scheduler::{Stage, SweepScheduler};
+use core_rs::workers::tone::{ToneSettings, ToneTarget};
+
+fn scheduler() -> (SweepScheduler, ManualClock) {
+    let clock = ManualClock::new(0);
+    let mut scheduler = SweepScheduler::with_clock(300, Arc::new(clock.clone()));
+    scheduler.set_tone(Some(ToneSettings { enabled: true, target: ToneTarget::Professional }));
+    scheduler.start();
+    (scheduler, clock)
+}
+
+/// Replays the sequential edits from `text`
+fn replay(text: &str, edits: &[core_rs::scheduler::StageEdit]) -> String {
+    edits.iter().fold(text.to_string(), |t, e| TextEdit::apply_all(&t, std::slice::from_ref(&e.edit)).unwrap())
+}
+
+#[test]
+fn typing_runs_noise_only() {
+    let (mut scheduler, clock) = scheduler();
+    clock.advance(100);
+    let text = "i saw taht cat ";
+    let out = scheduler.tick(text, text.len());
+    assert_eq!(out.stages, [Stage::Noise]);
+    assert_eq!(out.text, "i saw that cat ");
+    assert!(out.edits.iter().all(|e| e.stage == Stage::Noise));
+}
+
+#[test]
+fn short_pause_chains_noise_into_context() {
+    let (mut scheduler, clock) = scheduler();
+    clock.advance(300);
+    // Noise fixes "teh"; only then does Context see "the the"
+    let text = "we saw teh the cat ";
+    let out = scheduler.tick(text, text.len());
+    assert_eq!(out.stages, [Stage::Noise, Stage::Context]);
+    assert_eq!(out.text, "We saw the cat ");
+    assert_eq!(out.caret, out.text.len());
+    assert_eq!(replay(text, &out.edits), out.text);
+    assert!(out.edits.iter().any(|e| e.stage == Stage::Context));
+}
+
+#[test]
+fn long_pause_adds_tone() {
+    let (mut scheduler, clock) = scheduler();
+    clock.advance(2_000);
+    let text = "we can't come as teh car broke. ";
+    let out = scheduler.tick(text, text.len());
+    assert_eq!(out.stages, [Stage::Noise, Stage::Context, Stage::Tone]);
+    assert_eq!(out.text, "We cannot come as the car broke. ");
+    assert_eq!(replay(text, &out.edits), out.text);
+
+    // Tone disabled: the long pause stops at Context
+    scheduler.set_tone(Some(ToneSettings::default()));
+    scheduler.record_activity();
+    clock.advance(2_000);
+    assert_eq!(scheduler.due_stages(), [Stage::Noise, Stage::Context]);
+}
+
+#[test]
+fn non_english_text_gets_noise_only() {
+    let (mut scheduler, clock) = scheduler();
+    scheduler.set_language(Some(Language::French));
+    clock.advance(2_000);
+    let text = "je suis  ici i can't ";
+    let out = scheduler.tick(text, text.len());
+    assert_eq!(out.stages, [Stage::Noise]);
+    assert!(out.gated);
+    assert_eq!(out.text, "je suis ici i can't ");
+
+    // Low-confidence detections are treated as unknown
+    scheduler.set_language(None);
+    scheduler.detector.set_threshold(1.0);
+    assert_eq!(scheduler.language_for("hello there"), Language::Unknown);
+}
+
+#[test]
+fn noise_uses_the_rule_pack_of_each_run() {
+    let (mut scheduler, clock) = scheduler();
+    scheduler.set_language(Some(Language::Spanish));
+    clock.advance(100);
+    let text = "Como estas? Hola, despues te llamo ";
+    let out = scheduler.tick(text, text.len());
+    assert_eq!(out.text, "¿Como estas? Hola, después te llamo ");
+    assert_eq!(replay(text, &out.edits), out.text);
+}
+
+#[test]
+fn english_fixes_stay_out_of_other_languages() {
+    let (mut scheduler, clock) = scheduler();
+    clock.advance(100);
+    let spanish = "No sé si vienes mañana a la fiesta, pero te espero  allí ";
+    let out = scheduler.tick(spanish, spanish.len());
+    assert_eq!(out.language, Language::Spanish);
+    // " si " is Spanish here, not a typo of "is"; only the double space goes
+    assert_eq!(out.text, "No sé si vienes mañana a la fiesta, pero te espero allí ");
+
+    let french = "la personne dont je parle est partie hier soir avec ses amis ";
+    let out = scheduler.tick(french, french.len());
+    assert_eq!(out.language, Language::French);
+    assert_eq!(out.text, french);
+}
+
+#[test]
+fn mixed_document_gates_each_run() {
+    let (mut scheduler, clock) = scheduler();
+    clock.advance(300);
+    let text = "wir sehen uns morgen im büro, dann besprechen wir alles. we saw teh the cat ";
+    let out = scheduler.tick(text, text.len());
+    assert_eq!(out.language, Language::English);
+    assert_eq!(out.runs.iter().map(|r| r.language.clone()).collect::<Vec<_>>(), [Language::German, Language::English]);
+    assert_eq!(out.stages, [Stage::Noise, Stage::Context]);
+    // Each run gets its own pack ("Büro"; "im" is not the English "I'm");
+    // Context repairs the English sentence and leaves the German one alone
+    assert_eq!(out.text, "wir sehen uns morgen im Büro, dann besprechen wir alles. We saw the cat ");
+    assert!(out.gated);
+    assert_eq!(replay(text, &out.edits), out.text);
+}
+
+#[test]
+fn unchanged_text_is_not_swept_twice() {
+    let (mut scheduler, clock) = scheduler();
+    let text = "so i went home ";
+    clock.advance(300);
+    assert_eq!(scheduler.tick(text, text.len()).stages, [Stage::Noise, Stage::Context]);
+    clock.advance(100);
+    assert!(scheduler.tick(text, text.len()).stages.is_empty());
+    // A later stage becoming due runs the pipeline again
+    clock.advance(2_000);
+    assert_eq!(scheduler.tick(text, text.len()).stages.last(), Some(&Stage::Tone));
+}
+
+#[test]
+fn inactive_scheduler_does_nothing() {
+    let (mut scheduler, _) = scheduler();
+    scheduler.stop();
+    let out = scheduler.tick("teh end ", 8);
+    assert!(out.stages.is_empty() && out.edits.is_empty());
+    assert_eq!(out.text, "teh end ");
+}
@@ -1,13 +1,166 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+use std::sync::Arc;
+
+use core_rs::clock::ManualClock;
+use core_rs::confidence::ConfidenceConfig;
+use core_rs::diff::TextEdit;
+use core_rs::scheduler::{Stage, StageEdit};
+use core_rs::staging_buffer::{ProposalState, StagingBuffer, StagingOptions};
+
+fn proposal(text: &str, from: &str, to: &str, confidence: f32) -> StageEdit {
+    let start = text.find(from).unwrap();
+    StageEdit { stage: Stage::Noise, edit: TextEdit::new(start..start + from.len(), to), confidence }
+}
+
+fn gate() -> ConfidenceConfig {
+    ConfidenceConfig { base_threshold: 0.8 }
+}
+
+#[test]
+fn proposals_are_held_until_promoted() {
+    let text = "I saw teh cat and waht else ";
+    let mut buffer = StagingBuffer::default();
+    let sure = buffer.hold(text, proposal(text, "teh", "the", 0.95)).unwrap();
+    let unsure = buffer.hold(text, proposal(text, "waht", "what", 0.6)).unwrap();
+    let junk = buffer.hold(text, proposal(text, "cat", "dog", 0.1)).unwrap();
+    assert!(buffer.list().iter().all(|p| p.state == ProposalState::Hold));
+
+    assert_eq!(buffer.promote(text, text.len(), &gate()), 1);
+    assert_eq!(buffer.get(sure).unwrap().state, ProposalState::Commit);
+    assert_eq!(buffer.get(unsure).unwrap().state, ProposalState::Hold);
+    assert_eq!(buffer.get(junk).unwrap().state, ProposalState::Discard);
+
+    let edits = buffer.take_commits();
+    assert_eq!(TextEdit::apply_all(text, &edits).unwrap(), "I saw the cat and waht else ");
+    assert!(buffer.take_commits().is_empty(), "commits are handed out once");
+}
+
+#[test]
+fn same_range_replaces_the_pending_proposal() {
+    let text = "teh end ";
+    let mut buffer = StagingBuffer::default();
+    buffer.hold(text, proposal(text, "teh", "tea", 0.85)).unwrap();
+    let id = buffer.hold(text, proposal(text, "teh", "the", 0.9)).unwrap();
+    assert_eq!(buffer.list().len(), 1);
+    assert_eq!(buffer.get(id).unwrap().replacement, "the");
+    let out_of_range = StageEdit { stage: Stage::Noise, edit: TextEdit::new(5..20, "x"), confidence: 1.0 };
+    assert!(buffer.hold(text, out_of_range).is_none());
+}
+
+#[test]
+fn user_edits_invalidate_overlaps_and_shift_the_rest() {
+    let text = "one teh two waht three ";
+    let mut buffer = StagingBuffer::default();
+    let first = buffer.hold(text, proposal(text, "teh", "the", 0.9)).unwrap();
+    let second = buffer.hold(text, proposal(text, "waht", "what", 0.9)).unwrap();
+
+    // User retypes "teh" themselves
+    assert_eq!(buffer.on_user_edit(&TextEdit::new(4..7, "tee")), 1);
+    assert_eq!(buffer.get(first).unwrap().state, ProposalState::Discard);
+    // User inserts a word at the start: "waht" moves right
+    let edit = TextEdit::new(0..0, "so ");
+    assert_eq!(buffer.on_user_edit(&edit), 0);
+    let text = TextEdit::apply_all("one tee two waht three ", &[edit]).unwrap();
+    let moved = buffer.get(second).unwrap().range.clone();
+    assert_eq!(&text[moved], "waht");
+
+    // Typing right after a proposal does not touch it
+    assert_eq!(buffer.on_user_edit(&TextEdit::new(text.len()..text.len(), "x")), 0);
+}
+
+#[test]
+fn committed_proposals_roll_back_by_id() {
+    let text = "teh cat and teh dog ";
+    let mut buffer = StagingBuffer::default();
+    let a = buffer.hold(text, proposal(text, "teh cat", "the kitten", 0.95)).unwrap();
+    let b = buffer.hold(text, StageEdit { stage: Stage::Context, edit: TextEdit::new(12..15, "the"), confidence: 0.95 }).unwrap();
+    buffer.promote(text, text.len(), &gate());
+    let text = TextEdit::apply_all(text, &buffer.take_commits()).unwrap();
+    assert_eq!(text, "the kitten and the dog ");
+
+    // Undo the first; the second's range must still line up afterwards
+    let inverse = buffer.rollback(a).unwrap();
+    let text = TextEdit::apply_all(&text, &[inverse]).unwrap();
+    assert_eq!(text, "teh cat and the dog ");
+    assert_eq!(buffer.get(a).unwrap().state, ProposalState::Rollback);
+    assert!(buffer.rollback(a).is_none());
+
+    let inverse = buffer.rollback(b).unwrap();
+    assert_eq!(TextEdit::apply_all(&text, &[inverse]).unwrap(), "teh cat and teh dog ");
+}
+
+#[test]
+fn overlapping_commits_keep_the_first() {
+    let text = "the the cat ";
+    let mut buffer = StagingBuffer::default();
+    buffer.hold(text, StageEdit { stage: Stage::Context, edit: TextEdit::new(3..7, ""), confidence: 0.9 });
+    let cap = buffer.hold(text, StageEdit { stage: Stage::Context, edit: TextEdit::new(4..5, "T"), confidence: 0.9 }).unwrap();
+    buffer.promote(text, text.len(), &gate());
+    let edits = buffer.take_commits();
+    assert_eq!(TextEdit::apply_all(text, &edits).unwrap(), "the cat ");
+    assert_eq!(buffer.get(cap).unwrap().state, ProposalState::Discard);
+}
+
+#[test]
+fn caret_entry_withdraws_and_cleanup_expires() {
+    let clock = ManualClock::new(0);
+    let options = StagingOptions { ttl_ms: 1_000, ..StagingOptions::default() };
+    let mut buffer = StagingBuffer::with_clock(options, Arc::new(clock.clone()));
+    let text = "teh one waht two ";
+    let a = buffer.hold(text, proposal(text, "teh", "the", 0.9)).unwrap();
+    let b = buffer.hold(text, proposal(text, "waht", "what", 0.9)).unwrap();
+
+    assert_eq!(buffer.on_caret_move(2), 1);
+    assert_eq!(buffer.get(a).unwrap().state, ProposalState::Rollback);
+    buffer.cleanup();
+    assert!(buffer.get(a).is_none());
+    assert!(buffer.get(b).is_some());
+
+    clock.advance(1_001);
+    buffer.cleanup();
+    assert!(buffer.list().is_empty());
+}
+
+#[test]
+fn applied_commits_outlive_cleanup_until_released() {
+    let clock = ManualClock::new(0);
+    let options = StagingOptions { ttl_ms: 1_000, ..StagingOptions::default() };
+    let mut buffer = StagingBuffer::with_clock(options, Arc::new(clock.clone()));
+    let text = "teh one waht two ";
+    let a = buffer.hold(text, proposal(text, "teh", "the", 0.95)).unwrap();
+    let b = buffer.hold(text, proposal(text, "waht", "what", 0.95)).unwrap();
+    buffer.promote(text, text.len(), &gate());
+    let text = TextEdit::apply_all(text, &buffer.take_commits()).unwrap();
+
+    clock.advance(5_000);
+    buffer.cleanup();
+    let inverse = buffer.rollback(a).unwrap();
+    assert_eq!(TextEdit::apply_all(&text, &[inverse]).unwrap(), "teh one what two ");
+
+    assert!(buffer.release(b));
+    assert!(!buffer.release(b));
+    assert!(buffer.rollback(b).is_none());
+    buffer.cleanup();
+    assert!(buffer.list().is_empty());
+}
+
+#[test]
+fn promotion_scores_proposals_through_the_confidence_gate() {
+    // Same proposal, but the text around it is mostly symbols: τ_input holds it back
+    let garbled = "#$%^ &*() teh {}[] <>?! ";
+    let mut buffer = StagingBuffer::default();
+    let held = buffer.hold(garbled, proposal(garbled, "teh", "the", 0.95)).unwrap();
+    assert_eq!(buffer.promote(garbled, garbled.len(), &gate()), 0);
+    assert_eq!(buffer.get(held).unwrap().state, ProposalState::Hold);
+
+    // Tone faces a stricter bar than Noise, and a raised τ_commit holds both
+    let text = "we gonna be late ";
+    let tone = StageEdit { stage: Stage::Tone, ..proposal(text, "gonna", "are going to", 0.7) };
+    let noise = proposal(text, "we", "We", 0.9);
+    let mut buffer = StagingBuffer::default();
+    let tone = buffer.hold(text, tone).unwrap();
+    let noise = buffer.hold(text, noise).unwrap();
+    assert_eq!(buffer.promote(text, text.len(), &ConfidenceConfig { base_threshold: 0.95 }), 0);
+    assert_eq!(buffer.promote(text, text.len(), &gate()), 1);
+    assert_eq!(buffer.get(tone).unwrap().state, ProposalState::Hold);
+    assert_eq!(buffer.get(noise).unwrap().state, ProposalState::Commit);
+}
@@ -0,0 +1,188 @@
+#![allow(clippy::single_range_in_vec_init)]
+
+use core_rs::diff::TextEdit;
+use core_rs::tapestry::{Span, SpanKind, SpanMap, Tapestry};
+
+fn ranges(map: &SpanMap) -> Vec<(std::ops::Range<usize>, SpanKind)> {
+    map.iter().map(|s| (s.range(), s.kind)).collect()
+}
+
+/// Record and apply a system edit
+fn system(tapestry: &mut Tapestry, text: &mut String, edit: TextEdit, now_ms: u64) -> u64 {
+    let id = tapestry.record_system_edit(text, &edit, now_ms).unwrap();
+    *text = TextEdit::apply_all(text, &[edit]).unwrap();
+    id
+}
+
+fn user(tapestry: &mut Tapestry, text: &mut String, edit: TextEdit) {
+    tapestry.record_user_edit(&edit);
+    *text = TextEdit::apply_all(text, &[edit]).unwrap();
+}
+
+fn undo(tapestry: &mut Tapestry, text: &mut String) {
+    for edit in tapestry.undo_last_group() {
+        *text = TextEdit::apply_all(text, &[edit]).unwrap();
+    }
+}
+
+#[test]
+fn edits_group_by_time_bucket() {
+    let mut tapestry = Tapestry::new();
+    let mut text = String::from("teh cat adn dog ");
+    let a = system(&mut tapestry, &mut text, TextEdit::new(0..3, "the"), 1_000);
+    let b = system(&mut tapestry, &mut text, TextEdit::new(8..11, "and"), 1_100);
+    let c = system(&mut tapestry, &mut text, TextEdit::new(0..1, "T"), 1_400);
+    assert_eq!(a, b);
+    assert_ne!(b, c);
+    assert_eq!(tapestry.groups().len(), 2);
+    assert_eq!(tapestry.groups()[0].edits.len(), 2);
+    assert_eq!((tapestry.groups()[0].start_ms, tapestry.groups()[0].end_ms), (1_000, 1_100));
+    assert!(tapestry.record_system_edit(&text, &TextEdit::new(50..60, ""), 1_500).is_none());
+}
+
+#[test]
+fn user_edits_split_groups_and_are_never_undone() {
+    let mut tapestry = Tapestry::new();
+    let mut text = String::from("teh cat adn");
+    system(&mut tapestry, &mut text, TextEdit::new(0..3, "the"), 0);
+    user(&mut tapestry, &mut text, TextEdit::new(11..11, " a"));
+    system(&mut tapestry, &mut text, TextEdit::new(8..11, "and"), 10);
+    assert_eq!(tapestry.groups().len(), 2, "user typing closes the group");
+
+    undo(&mut tapestry, &mut text);
+    assert_eq!(text, "the cat adn a");
+    undo(&mut tapestry, &mut text);
+    assert_eq!(text, "teh cat adn a");
+    assert!(tapestry.undo_last_group().is_empty());
+}
+
+#[test]
+fn undo_rebases_over_later_typing() {
+    let mut tapestry = Tapestry::new();
+    let mut text = String::from("I saw teh cat");
+    system(&mut tapestry, &mut text, TextEdit::new(6..9, "the"), 0);
+    user(&mut tapestry, &mut text, TextEdit::new(0..0, "Yes, "));
+    let end = text.len();
+    user(&mut tapestry, &mut text, TextEdit::new(end..end, " today"));
+    user(&mut tapestry, &mut text, TextEdit::new(15..15, "big "));
+    assert_eq!(text, "Yes, I saw the big cat today");
+
+    let inverse = tapestry.peek_last_group();
+    assert_eq!(inverse, [TextEdit::new(11..14, "teh")]);
+    undo(&mut tapestry, &mut text);
+    assert_eq!(text, "Yes, I saw teh big cat today");
+}
+
+#[test]
+fn whole_group_reverts_in_one_step() {
+    let mut tapestry = Tapestry::new();
+    let original = "teh cat  adn the the dog ";
+    let mut text = String::from(original);
+    system(&mut tapestry, &mut text, TextEdit::new(0..3, "The"), 0);
+    system(&mut tapestry, &mut text, TextEdit::new(7..8, ""), 20);
+    system(&mut tapestry, &mut text, TextEdit::new(8..11, "and"), 40);
+    system(&mut tapestry, &mut text, TextEdit::new(15..19, ""), 60);
+    assert_eq!(text, "The cat and the dog ");
+
+    let inverse = tapestry.peek_last_group();
+    assert!(inverse.windows(2).all(|w| w[0].range.start >= w[1].range.start), "right to left");
+    assert_eq!(TextEdit::apply_all(&text, &inverse).unwrap(), original);
+    undo(&mut tapestry, &mut text);
+    assert_eq!(text, original);
+}
+
+#[test]
+fn edits_rewritten_by_the_user_are_left_alone() {
+    let mut tapestry = Tapestry::new();
+    let mut text = String::from("teh cat adn dog");
+    system(&mut tapestry, &mut text, TextEdit::new(0..3, "the"), 0);
+    system(&mut tapestry, &mut text, TextEdit::new(8..11, "and"), 10);
+    // The user retypes part of "and"
+    user(&mut tapestry, &mut text, TextEdit::new(9..10, "N"));
+    assert!(!tapestry.groups()[0].edits[1].revertible);
+    undo(&mut tapestry, &mut text);
+    assert_eq!(text, "teh cat aNd dog");
+}
+
+#[test]
+fn marking_overwrites_and_coalesces() {
+    use SpanKind::*;
+    let mut map = SpanMap::new();
+    map.mark(0..10, Validated, 1);
+    map.mark(10..20, Validated, 2);
+    assert_eq!(ranges(&map), [(0..20, Validated)], "touching spans of one kind merge");
+    assert_eq!(map.iter().next().unwrap().applied_at_ms, 2);
+
+    map.mark(5..8, Animated, 3);
+    map.mark(20..25, Unvalidated, 3);
+    assert_eq!(ranges(&map), [(0..5, Validated), (5..8, Animated), (8..20, Validated), (20..25, Unvalidated)]);
+
+    // Animation ends: the region is validated again and rejoins its neighbours
+    map.mark(5..8, Validated, 4);
+    assert_eq!(ranges(&map), [(0..20, Validated), (20..25, Unvalidated)]);
+
+    map.clear(3..22);
+    assert_eq!(ranges(&map), [(0..3, Validated), (22..25, Unvalidated)]);
+    map.mark(4..4, Validated, 5);
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn queries_clip_to_the_range() {
+    use SpanKind::*;
+    let mut map = SpanMap::new();
+    map.mark(0..10, Validated, 0);
+    map.mark(10..15, Unvalidated, 0);
+    map.mark(20..30, Validated, 0);
+    assert_eq!(map.query(5..25, Validated), [5..10, 20..25]);
+    assert_eq!(map.query(5..25, Unvalidated), [10..15]);
+    assert!(map.query(15..20, Validated).is_empty());
+    assert_eq!(map.spans_in(12..22).iter().map(|s| s.range()).collect::<Vec<_>>(), [12..15, 20..22]);
+    assert_eq!(map.kind_at(9), Some(Validated));
+    assert_eq!(map.kind_at(10), Some(Unvalidated));
+    assert_eq!(map.kind_at(17), None);
+    assert_eq!(map.kind_at(30), None);
+}
+
+#[test]
+fn spans_shift_and_split_with_edits() {
+    use SpanKind::*;
+    let mut map = SpanMap::new();
+    map.mark(0..10, Validated, 0);
+    map.mark(20..30, Validated, 0);
+
+    // Insertion inside a span splits it; later spans shift
+    map.apply_edit(&TextEdit::new(4..4, "abc"));
+    assert_eq!(ranges(&map), [(0..4, Validated), (7..13, Validated), (23..33, Validated)]);
+
+    // Insertion at a span's end leaves it alone; at its start shifts it
+    map.apply_edit(&TextEdit::new(13..13, "x"));
+    map.apply_edit(&TextEdit::new(24..24, "yy"));
+    assert_eq!(ranges(&map), [(0..4, Validated), (7..13, Validated), (26..36, Validated)]);
+
+    // Deleting the inserted text re-joins the split span
+    map.apply_edit(&TextEdit::new(4..7, ""));
+    assert_eq!(ranges(&map), [(0..10, Validated), (23..33, Validated)]);
+
+    // A replacement across two spans trims both
+    map.apply_edit(&TextEdit::new(8..25, "Z"));
+    assert_eq!(ranges(&map), [(0..8, Validated), (9..17, Validated)]);
+
+    // Deleting a whole span removes it
+    map.apply_edit(&TextEdit::new(9..17, ""));
+    assert_eq!(ranges(&map), [(0..8, Validated)]);
+}
+
+#[test]
+fn tapestry_spans_follow_recorded_edits() {
+    let mut tapestry = Tapestry::new();
+    let mut text = String::from("teh cat sat");
+    tapestry.push(Span { start: 8, end: 11, kind: SpanKind::Validated, applied_at_ms: 0 });
+    system(&mut tapestry, &mut text, TextEdit::new(0..3, "the big"), 0);
+    assert_eq!(tapestry.spans.query(0..text.len(), SpanKind::Validated), [12..15]);
+    assert_eq!(&text[12..15], "sat");
+    user(&mut tapestry, &mut text, TextEdit::new(0..0, "So "));
+    undo(&mut tapestry, &mut text);
+    assert_eq!(text, "So teh cat sat");
+    assert_eq!(tapestry.spans.query(0..text.len(), SpanKind::Validated), [11..14]);
+}
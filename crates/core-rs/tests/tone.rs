@@ -1,13 +1,120 @@
-/*
-╔══════════════════════════════════════════════════════╗
-║  ░  R U S T   T E S T   S T U B  ░░░░░░░░░░░░░░░░░  ║
-║                                                      ║
-║           ╌╌  P L A C E H O L D E R  ╌╌              ║
-╚══════════════════════════════════════════════════════╝
-  • WHAT ▸ Test stub for $f
-  • WHY  ▸ Satisfy doc:check during migration
-  • HOW  ▸ Replace with real tests
-*/
-
-#[test]
-fn stub_${f%.rs}() { assert!(true); }
+mod common;
+
+use core_rs::diff::TextEdit;
+use core_rs::engine::{Engine, EngineConfig, SkipReason};
+use core_rs::ffi::mind_type_set_tone;
+use core_rs::language_detection::Language;
+use core_rs::workers::tone::{self, detect_baseline, ToneSettings, ToneTarget, ToneWorker};
+
+use common::typed;
+
+fn rewrite(target: ToneTarget, text: &str) -> String {
+    let worker = ToneWorker::new(ToneSettings { enabled: true, target });
+    let edits: Vec<TextEdit> = worker.plan(text, text.len()).into_iter().map(|p| p.edit).collect();
+    TextEdit::apply_all(text, &edits).unwrap()
+}
+
+#[test]
+fn baseline_separates_casual_and_formal_text() {
+    let casual = detect_baseline("hey, I'm gonna be late! it's cool, don't wait. maybe tomorrow?");
+    let formal = detect_baseline("We cannot attend; however, the report is complete, reviewed, and filed.");
+    assert!(casual.formality < formal.formality, "{:?} vs {:?}", casual, formal);
+    assert!(casual.warmth > formal.warmth);
+    assert_eq!(casual.contractions, 1.0);
+    assert_eq!(formal.contractions, 0.0);
+    assert!(casual.hedging > 0.0 && formal.hedging == 0.0);
+}
+
+#[test]
+fn professional_expands_contractions_and_slang() {
+    assert_eq!(
+        rewrite(ToneTarget::Professional, "I'm gonna send it. It’s done, we can't wait. Thanks"),
+        "I am going to send it. It is done, we cannot wait. Thanks"
+    );
+}
+
+#[test]
+fn casual_introduces_contractions() {
+    assert_eq!(
+        rewrite(ToneTarget::Casual, "It is ready and we are happy that you are here. Done"),
+        "It's ready and we're happy that you're here. Done"
+    );
+}
+
+#[test]
+fn none_or_disabled_proposes_nothing() {
+    assert_eq!(rewrite(ToneTarget::None, "I'm gonna go now"), "I'm gonna go now");
+    let disabled = ToneWorker::new(ToneSettings { enabled: false, target: ToneTarget::Professional });
+    assert!(disabled.plan("I'm gonna go now", 16).is_empty());
+}
+
+#[test]
+fn proposals_end_before_the_caret() {
+    let worker = ToneWorker::new(ToneSettings { enabled: true, target: ToneTarget::Professional });
+    let text = "we're fine and it's done";
+    // "it'" is still being typed at the caret
+    let caret = text.find("it's").unwrap() + 3;
+    let proposals = worker.plan(text, caret);
+    assert_eq!(proposals.len(), 1);
+    assert!(proposals.iter().all(|p| p.edit.range.end < caret && p.confidence > 0.8));
+}
+
+#[test]
+fn set_tone_updates_engine_state() {
+    let target = "Professional";
+    assert!(mind_type_set_tone(true, target.as_ptr(), target.len()));
+    let mut engine = Engine::new(EngineConfig::default());
+    assert_eq!(engine.tone(), ToneSettings { enabled: true, target: ToneTarget::Professional });
+
+    let bogus = "Sarcastic";
+    assert!(!mind_type_set_tone(true, bogus.as_ptr(), bogus.len()));
+    assert_eq!(tone::global_tone().target, ToneTarget::Professional);
+
+    engine.set_tone(Some(ToneSettings { enabled: true, target: ToneTarget::Casual }));
+    assert_eq!(engine.tone().target, ToneTarget::Casual);
+    engine.set_tone(None);
+    assert_eq!(engine.tone().target, ToneTarget::Professional);
+
+    // The setting reaches engine output on a long pause
+    let text = "we can't come as the car broke. ";
+    engine.observe(typed(text.len(), 0));
+    let out = engine.tick_at(text, text.len(), 2_000);
+    tone::set_global_tone(ToneSettings::default());
+    assert_eq!(TextEdit::apply_all(text, &out.edits).unwrap(), "we cannot come as the car broke. ");
+}
+
+#[test]
+fn engine_tick_runs_tone_on_long_pauses_only() {
+    let text = "we can't make it, we're gonna be late. ";
+    let mut engine = Engine::new(EngineConfig::default());
+    engine.set_tone(Some(ToneSettings { enabled: true, target: ToneTarget::Professional }));
+    engine.observe(typed(text.len(), 0));
+    // Short pause: no tone stage
+    let out = engine.tick_at(text, text.len(), 600);
+    assert_eq!(out.diagnostics.skipped, Some(SkipReason::NoCorrection));
+
+    let out = engine.tick_at(text, text.len(), 2_000);
+    assert_eq!(TextEdit::apply_all(text, &out.edits).unwrap(), "we cannot make it, we are going to be late. ");
+
+    // Non-English text is left alone
+    let mut engine = Engine::new(EngineConfig::default());
+    engine.set_tone(Some(ToneSettings { enabled: true, target: ToneTarget::Professional }));
+    let text = "Je ne peux pas venir car la voiture est en panne. ";
+    engine.observe(typed(text.len(), 0));
+    assert!(engine.tick_at(text, text.len(), 2_000).edits.is_empty());
+}
+
+#[test]
+fn engine_tick_gates_tone_per_language_run() {
+    // The region's last run is French, but its English sentence still gets tone
+    let text = "we can't make it, we're gonna be late. Je ne peux pas venir car la voiture est en panne. ";
+    let mut engine = Engine::new(EngineConfig::default());
+    engine.set_tone(Some(ToneSettings { enabled: true, target: ToneTarget::Professional }));
+    engine.observe(typed(text.len(), 0));
+    let out = engine.tick_at(text, text.len(), 2_000);
+    assert_eq!(out.diagnostics.language.map(|(l, _)| l), Some(Language::French));
+    assert_eq!(
+        TextEdit::apply_all(text, &out.edits).unwrap(),
+        "we cannot make it, we are going to be late. Je ne peux pas venir car la voiture est en panne. "
+    );
+}
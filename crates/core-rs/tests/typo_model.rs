@@ -0,0 +1,127 @@
+use core_rs::engine::{Engine, EngineConfig};
+use core_rs::ffi::mind_type_set_keyboard_layout;
+use core_rs::scheduler::SweepScheduler;
+use core_rs::typo_model::{self, KeyboardLayout, TypoModel};
+use core_rs::workers::noise::{KeyboardTypoRule, NoiseInput, NoiseRegistry};
+
+fn top(model: &TypoModel, typed: &str, candidates: &[&'static str]) -> &'static str {
+    model.rank(typed, candidates.iter().copied())[0].word
+}
+
+#[test]
+fn ranks_by_physical_plausibility() {
+    let qwerty = TypoModel::new(KeyboardLayout::Qwerty);
+    assert_eq!(top(&qwerty, "teh", &["ten", "tea", "tech", "the"]), "the");
+    assert_eq!(top(&qwerty, "wprld", &["word", "would", "wild", "world"]), "world");
+    // A dropped double letter beats a far-off substitution
+    assert_eq!(top(&qwerty, "leter", &["later", "letter"]), "letter");
+
+    let ranked = qwerty.rank("wprld", ["world", "word"]);
+    assert!(ranked[0].posterior > 0.9);
+    assert!((ranked.iter().map(|c| c.posterior).sum::<f32>() - 1.0).abs() < 1e-4);
+    assert_eq!(qwerty.log_likelihood("World", "world"), 0.0);
+}
+
+#[test]
+fn priors_can_outweigh_the_channel() {
+    let model = TypoModel::new(KeyboardLayout::Qwerty);
+    let ranked = model.rank_with_priors("teh", [("the", 0.001), ("ten", 0.5)]);
+    assert_eq!(ranked[0].word, "ten");
+}
+
+#[test]
+fn adjacency_follows_the_layout() {
+    let (qwerty, qwertz, azerty, dvorak) =
+        (KeyboardLayout::Qwerty, KeyboardLayout::Qwertz, KeyboardLayout::Azerty, KeyboardLayout::Dvorak);
+    assert!(qwerty.is_adjacent('o', 'p') && !dvorak.is_adjacent('o', 'p'));
+    assert!(qwertz.is_adjacent('z', 'u') && !qwerty.is_adjacent('z', 'u'));
+    assert!(azerty.is_adjacent('a', 'z') && azerty.is_adjacent('q', 's'));
+    assert!(dvorak.is_adjacent('a', 'o'));
+    assert!(!qwerty.is_adjacent('a', 'a') && !qwerty.is_adjacent('a', 'é'));
+
+    // The same slip is far less plausible where the keys are not neighbours
+    let on = |layout| TypoModel::new(layout).log_likelihood("wprld", "world");
+    assert!(on(qwerty) > on(dvorak) + 2.0);
+}
+
+#[test]
+fn layout_names_round_trip() {
+    for layout in [KeyboardLayout::Qwerty, KeyboardLayout::Qwertz, KeyboardLayout::Azerty, KeyboardLayout::Dvorak] {
+        assert_eq!(KeyboardLayout::from_name(layout.name()), Some(layout));
+    }
+    assert_eq!(KeyboardLayout::from_name(" dvorak "), Some(KeyboardLayout::Dvorak));
+    assert_eq!(KeyboardLayout::from_name("colemak"), None);
+}
+
+#[test]
+fn noise_rule_fixes_out_of_vocabulary_words() {
+    let vocabulary = ["hello", "world", "word", "the", "they", "then", "is", "ok"];
+    let mut registry = NoiseRegistry::empty();
+    registry.register(Box::new(KeyboardTypoRule::new(TypoModel::new(KeyboardLayout::Qwerty), vocabulary)));
+    let fix = |registry: &NoiseRegistry, text: &str| registry.sweep(text, text.len()).0;
+
+    assert_eq!(fix(&registry, "hello wprld teh end "), "hello world the end ");
+    // Known words, names and the word at the caret are left alone
+    assert_eq!(fix(&registry, "the world is ok "), "the world is ok ");
+    assert_eq!(fix(&registry, "hello Wprld "), "hello Wprld ");
+    assert!(registry.transform(&NoiseInput::new("hello wprld", 11)).is_none());
+
+    // On Dvorak o/p are far apart, so the slip is no longer plausible enough
+    registry.set_keyboard_layout(KeyboardLayout::Dvorak);
+    assert_eq!(fix(&registry, "hello wprld "), "hello wprld ");
+}
+
+#[test]
+fn engine_config_selects_the_layout() {
+    let config = EngineConfig { keyboard_layout: KeyboardLayout::Azerty, ..EngineConfig::default() };
+    let mut engine = Engine::new(config);
+    assert_eq!(engine.typo_model().layout, KeyboardLayout::Azerty);
+    engine.set_keyboard_layout(KeyboardLayout::Qwertz);
+    assert_eq!(engine.config().keyboard_layout, KeyboardLayout::Qwertz);
+}
+
+#[test]
+fn default_config_is_qwerty() {
+    assert_eq!(EngineConfig::default().keyboard_layout, KeyboardLayout::Qwerty);
+    assert_eq!(SweepScheduler::new(500).keyboard_layout(), KeyboardLayout::Qwerty);
+}
+
+#[test]
+fn scheduler_forwards_the_layout_to_its_noise_rules() {
+    let mut scheduler = SweepScheduler::new(500);
+    let model = TypoModel::new(KeyboardLayout::Qwerty);
+    scheduler.noise.register(Box::new(KeyboardTypoRule::new(model, ["hello", "world"])));
+    assert_eq!(scheduler.noise.sweep("hello wprld ", 12).0, "hello world ");
+    scheduler.set_keyboard_layout(KeyboardLayout::Dvorak);
+    assert_eq!(scheduler.keyboard_layout(), KeyboardLayout::Dvorak);
+    assert_eq!(scheduler.noise.sweep("hello wprld ", 12).0, "hello wprld ");
+}
+
+#[test]
+fn ffi_layout_reaches_running_engines_and_schedulers() {
+    let mut engine = Engine::new(EngineConfig::default());
+    let mut scheduler = SweepScheduler::new(500);
+    let name = "AZERTY";
+    assert!(mind_type_set_keyboard_layout(name.as_ptr(), name.len()));
+    assert_eq!(typo_model::global_layout(), KeyboardLayout::Azerty);
+    let bad = "colemak";
+    assert!(!mind_type_set_keyboard_layout(bad.as_ptr(), bad.len()));
+    assert_eq!(typo_model::global_layout(), KeyboardLayout::Azerty);
+
+    // Picked up on the next tick
+    assert_eq!(engine.config().keyboard_layout, KeyboardLayout::Qwerty);
+    engine.tick("hello", 5);
+    scheduler.tick("hello", 5);
+    assert_eq!(engine.typo_model().layout, KeyboardLayout::Azerty);
+    assert_eq!(scheduler.keyboard_layout(), KeyboardLayout::Azerty);
+    // A later local choice stands until the host reports again
+    engine.set_keyboard_layout(KeyboardLayout::Qwertz);
+    engine.tick("hello", 5);
+    assert_eq!(engine.config().keyboard_layout, KeyboardLayout::Qwertz);
+    // Defaults stay QWERTY whatever the host reported
+    assert_eq!(EngineConfig::default().keyboard_layout, KeyboardLayout::Qwerty);
+
+    typo_model::set_global_layout(KeyboardLayout::Qwerty);
+    engine.tick("hello", 5);
+    assert_eq!(engine.config().keyboard_layout, KeyboardLayout::Qwerty);
+}